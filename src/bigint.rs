@@ -0,0 +1,393 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+/// An integer of any size, used when `Int` arithmetic overflows.
+///
+/// The magnitude is stored in base 2^32, least significant digit first,
+/// without leading zeros, so zero has no digits and every value has a single
+/// representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+const BASE: u64 = 1 << 32;
+
+/// Largest power of ten in a digit, to print and parse nine decimals at a time.
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        let mag = n.unsigned_abs();
+        Self::new(n < 0, vec![mag as u32, (mag >> 32) as u32])
+    }
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Parses unsigned digits in base `radix`, from 2 to 36.
+    pub fn parse_radix(s: &str, radix: u32) -> Option<Self> {
+        let mut digits = vec![];
+        for c in s.chars() {
+            mul_small_add(&mut digits, radix, c.to_digit(radix)?);
+        }
+        (!s.is_empty()).then(|| Self::new(false, digits))
+    }
+
+    /// Parses an optionally signed run of decimal digits.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut digits = vec![];
+        let first = s.len() % 9;
+        let chunks = std::iter::once(&s[..first])
+            .chain((first..s.len()).step_by(9).map(|at| &s[at..at + 9]));
+        for chunk in chunks.filter(|chunk| !chunk.is_empty()) {
+            let scale = 10u32.pow(chunk.len() as u32);
+            mul_small_add(&mut digits, scale, chunk.parse().unwrap());
+        }
+        Some(Self::new(negative, digits))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let mag = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &d| (acc << 32) | d as u64);
+        match self.negative {
+            false => i64::try_from(mag).ok(),
+            true => 0i64.checked_sub_unsigned(mag),
+        }
+    }
+
+    /// The integer a float equals exactly, if it is finite and whole.
+    pub fn from_f64(n: f64) -> Option<Self> {
+        if !n.is_finite() || n.fract() != 0.0 {
+            return None;
+        }
+        let bits = n.abs().to_bits();
+        let exponent = (bits >> 52) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        // Whole floats below 2^53 are exact in an i64.
+        if exponent < 1075 {
+            return Some((n as i64).into());
+        }
+        // n is (2^52 + fraction) * 2^shift.
+        let shift = (exponent - 1075) as usize;
+        let mantissa = ((1u64 << 52) | fraction) as u128;
+        let shifted = mantissa << (shift % 32);
+        let mut digits = vec![0; shift / 32];
+        digits.extend([
+            shifted as u32,
+            (shifted >> 32) as u32,
+            (shifted >> 64) as u32,
+        ]);
+        Some(Self::new(n < 0.0, digits))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let mag = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &d| acc * BASE as f64 + d as f64);
+        if self.negative {
+            -mag
+        } else {
+            mag
+        }
+    }
+
+    /// Quotient truncated toward zero and the remainder, which takes the sign
+    /// of `self`, as for `i64`. `other` must not be zero.
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        let (quotient, remainder) = div_rem_mag(&self.digits, &other.digits);
+        (
+            Self::new(self.negative != other.negative, quotient),
+            Self::new(self.negative, remainder),
+        )
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut digits = self.digits.clone();
+        let mut chunks = vec![];
+        while !digits.is_empty() {
+            chunks.push(div_small(&mut digits, DECIMAL_CHUNK));
+        }
+        let mut s = String::from(if self.negative { "-" } else { "" });
+        match chunks.split_last() {
+            None => s.push('0'),
+            Some((first, rest)) => {
+                s.push_str(&first.to_string());
+                for chunk in rest.iter().rev() {
+                    s.push_str(&format!("{chunk:09}"));
+                }
+            }
+        }
+        s
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.digits, &other.digits),
+            (true, true) => cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_mag(&self.digits, &other.digits));
+        }
+        match cmp_mag(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, sub_mag(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub_mag(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.digits.iter().enumerate() {
+                let t = digits[i + j] as u64 + a as u64 * b as u64 + carry;
+                digits[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, digits)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut digits = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &d) in long.iter().enumerate() {
+        let t = d as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        digits.push(t as u32);
+        carry = t >> 32;
+    }
+    digits.push(carry as u32);
+    digits
+}
+
+/// `a - b`, where `a` is at least `b`.
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &d) in a.iter().enumerate() {
+        let mut t = d as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = (t < 0) as i64;
+        t += borrow * BASE as i64;
+        digits.push(t as u32);
+    }
+    digits
+}
+
+/// `digits = digits * factor + add`.
+fn mul_small_add(digits: &mut Vec<u32>, factor: u32, add: u32) {
+    let mut carry = add as u64;
+    for d in digits.iter_mut() {
+        let t = *d as u64 * factor as u64 + carry;
+        *d = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+}
+
+/// Divides `digits` in place by `divisor` and returns the remainder.
+fn div_small(digits: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for d in digits.iter_mut().rev() {
+        let t = (rem << 32) | *d as u64;
+        *d = (t / divisor as u64) as u32;
+        rem = t % divisor as u64;
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    rem as u32
+}
+
+/// Schoolbook binary long division, one bit of the dividend at a time.
+fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let rem = div_small(&mut quotient, *divisor);
+        return (quotient, vec![rem]);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem: Vec<u32> = vec![];
+    for bit in (0..a.len() * 32).rev() {
+        // rem = rem << 1 | next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for d in rem.iter_mut() {
+            let next = *d >> 31;
+            *d = (*d << 1) | carry;
+            carry = next;
+        }
+        if carry > 0 {
+            rem.push(carry);
+        }
+        if cmp_mag(&rem, b) != Ordering::Less {
+            rem = sub_mag(&rem, b);
+            while rem.last() == Some(&0) {
+                rem.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, rem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(n: u128) -> Vec<u32> {
+        BigInt::new(false, (0..4).map(|i| (n >> (32 * i)) as u32).collect()).digits
+    }
+
+    fn big(s: &str) -> BigInt {
+        BigInt::parse(s).unwrap()
+    }
+
+    #[test]
+    fn div_rem_mag_matches_u128() {
+        let values = [
+            1,
+            7,
+            u32::MAX as u128,
+            1 << 32,
+            u64::MAX as u128,
+            (1 << 64) + 12345,
+            u128::MAX / 3,
+            u128::MAX,
+        ];
+        for a in values {
+            for b in values {
+                let (q, r) = div_rem_mag(&digits(a), &digits(b));
+                assert_eq!(BigInt::new(false, q).digits, digits(a / b), "{a} / {b}");
+                assert_eq!(BigInt::new(false, r).digits, digits(a % b), "{a} % {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn div_rem_truncates_toward_zero() {
+        for (a, b) in [(7, 2), (-7, 2), (7, -2), (-7, -2), (i64::MIN, 3)] {
+            let (q, r) = BigInt::from(a).div_rem(&BigInt::from(b));
+            assert_eq!((q.to_i64(), r.to_i64()), (Some(a / b), Some(a % b)));
+        }
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        for s in [
+            "0",
+            "-1",
+            "999999999",
+            "1000000000",
+            "-123456789012345678901234567890",
+            "340282366920938463463374607431768211456",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("+42"), BigInt::from(42));
+        assert_eq!(big("-0"), BigInt::from(0));
+        for s in ["", "-", "+", "1_000", "12a", " 1"] {
+            assert_eq!(BigInt::parse(s), None, "{s:?}");
+        }
+        assert_eq!(BigInt::parse_radix("ff", 16), Some(BigInt::from(255)));
+        assert_eq!(BigInt::parse_radix("12", 2), None);
+    }
+
+    #[test]
+    fn from_f64_is_exact() {
+        assert_eq!(BigInt::from_f64(-3.0), Some(BigInt::from(-3)));
+        assert_eq!(
+            BigInt::from_f64(2f64.powi(63)),
+            Some(big("9223372036854775808"))
+        );
+        assert_eq!(
+            BigInt::from_f64(-(2f64.powi(100))),
+            Some(big("-1267650600228229401496703205376"))
+        );
+        assert_eq!(BigInt::from_f64(1e308).map(|n| n.to_f64()), Some(1e308));
+        for n in [0.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(BigInt::from_f64(n), None, "{n}");
+        }
+    }
+}
@@ -0,0 +1,157 @@
+pub const USAGE: &str = "\
+Usage: mlox [options] [script [args...]]
+       mlox run <script> [-- args...]
+       mlox -e <code> [-- args...]
+
+Options:
+  -e, --eval <code>  Run the code given on the command line
+  -q, --quiet        Do not print the banners
+  -V, --version      Print the version
+  -h, --help         Print this message
+
+A script named '-' is read from stdin. Script arguments are
+available to the program in the global list ARGV.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Prompt,
+    File(String),
+    Stdin,
+    Eval(String),
+    Version,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub quiet: bool,
+
+    /// Arguments passed through to the script as `ARGV`.
+    pub script_args: Vec<String>,
+}
+
+impl Cli {
+    /// Parses the process arguments, `args[0]` being the program name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut cli = Self {
+            command: Command::Prompt,
+            quiet: false,
+            script_args: vec![],
+        };
+        let mut args = args.iter().skip(1);
+        let mut script = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-q" | "--quiet" => cli.quiet = true,
+                "-V" | "--version" => return Ok(cli.with(Command::Version)),
+                "-h" | "--help" => return Ok(cli.with(Command::Help)),
+                "-e" | "--eval" => {
+                    let code = args.next().ok_or("Missing code after '-e'.")?;
+                    cli.command = Command::Eval(code.clone());
+                    break;
+                }
+                "run" if script.is_none() => {
+                    let path = args.next().ok_or("Missing script after 'run'.")?;
+                    script = Some(path.clone());
+                    break;
+                }
+                "--" => break,
+                flag if flag.starts_with('-') && flag != "-" => {
+                    return Err(format!("Unknown option '{flag}'."));
+                }
+                path => {
+                    script = Some(path.to_owned());
+                    break;
+                }
+            }
+        }
+
+        if let Some(path) = script {
+            cli.command = match path.as_str() {
+                "-" => Command::Stdin,
+                _ => Command::File(path),
+            };
+        }
+        cli.script_args = args.cloned().collect();
+        if cli.script_args.first().is_some_and(|arg| arg == "--") {
+            cli.script_args.remove(0);
+        }
+        if cli.command == Command::Prompt && !cli.script_args.is_empty() {
+            return Err("Script arguments given without a script.".into());
+        }
+        Ok(cli)
+    }
+
+    fn with(mut self, command: Command) -> Self {
+        self.command = command;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        let args: Vec<String> = ["mlox"].iter().chain(args).map(|&a| a.into()).collect();
+        Cli::parse(&args)
+    }
+
+    fn cli(command: Command, quiet: bool, script_args: &[&str]) -> Cli {
+        Cli {
+            command,
+            quiet,
+            script_args: script_args.iter().map(|&a| a.into()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&[]), Ok(cli(Command::Prompt, false, &[])));
+        assert_eq!(parse(&["-q"]), Ok(cli(Command::Prompt, true, &[])));
+        assert_eq!(
+            parse(&["a.mlox"]),
+            Ok(cli(Command::File("a.mlox".into()), false, &[]))
+        );
+        assert_eq!(
+            parse(&["run", "-q"]),
+            Ok(cli(Command::File("-q".into()), false, &[]))
+        );
+        assert_eq!(parse(&["-"]), Ok(cli(Command::Stdin, false, &[])));
+        assert_eq!(
+            parse(&["--quiet", "-e", "print 1;"]),
+            Ok(cli(Command::Eval("print 1;".into()), true, &[]))
+        );
+        assert_eq!(parse(&["-V", "x"]), Ok(cli(Command::Version, false, &[])));
+        assert_eq!(parse(&["--help"]), Ok(cli(Command::Help, false, &[])));
+    }
+
+    #[test]
+    fn passes_the_rest_to_the_script() {
+        assert_eq!(
+            parse(&["a.mlox", "-q", "x"]),
+            Ok(cli(Command::File("a.mlox".into()), false, &["-q", "x"]))
+        );
+        assert_eq!(
+            parse(&["run", "a.mlox", "--", "--", "x"]),
+            Ok(cli(Command::File("a.mlox".into()), false, &["--", "x"]))
+        );
+        assert_eq!(
+            parse(&["-e", "print ARGV;", "--", "1"]),
+            Ok(cli(Command::Eval("print ARGV;".into()), false, &["1"]))
+        );
+        assert_eq!(
+            parse(&["--", "a.mlox"]),
+            Err("Script arguments given without a script.".into())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        assert_eq!(parse(&["-x"]), Err("Unknown option '-x'.".into()));
+        assert_eq!(parse(&["-e"]), Err("Missing code after '-e'.".into()));
+        assert_eq!(parse(&["run"]), Err("Missing script after 'run'.".into()));
+    }
+}
@@ -0,0 +1,83 @@
+use std::{rc::Rc, sync::Mutex};
+
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, validate::Validator, Context,
+    Helper,
+};
+
+use unicode_ident::is_xid_continue;
+
+use crate::{environ::Environment, scanner};
+
+/// Tab completion for the prompt: keywords, global names, and the members
+/// of a global after `name.`.
+pub struct MloxHelper {
+    env: Rc<Mutex<Environment>>,
+}
+
+impl MloxHelper {
+    pub fn new(env: Rc<Mutex<Environment>>) -> Self {
+        Self { env }
+    }
+
+    fn candidates(&self, line: &str, start: usize) -> Vec<String> {
+        let env = self.env.lock().unwrap();
+        let globals = env.values();
+
+        if let Some(before) = line[..start].strip_suffix('.') {
+            let object = before
+                .rsplit(|c: char| !is_xid_continue(c))
+                .next()
+                .unwrap_or_default();
+            return globals
+                .iter()
+                .find(|(name, _)| name.as_str() == object)
+                .map(|(_, value)| value.members())
+                .unwrap_or_default();
+        }
+
+        let mut names = scanner::keywords()
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        names.extend(globals.iter().map(|(name, _)| name.to_string()));
+        names
+    }
+}
+
+impl Completer for MloxHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !is_xid_continue(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let prefix = &line[start..pos];
+
+        let mut candidates = self
+            .candidates(line, start)
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for MloxHelper {
+    type Hint = String;
+}
+
+impl Highlighter for MloxHelper {}
+
+impl Validator for MloxHelper {}
+
+impl Helper for MloxHelper {}
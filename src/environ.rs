@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
-use crate::token::{Object, Token};
+use crate::{
+    error::RuntimeError,
+    token::{Object, Token},
+};
 
+#[derive(Default)]
 pub struct Environment {
     pub enclosing: Option<Box<Environment>>,
     values: HashMap<String, Object>,
@@ -9,34 +13,57 @@ pub struct Environment {
 
 impl Environment {
     pub fn new() -> Self {
-        Self {
-            enclosing: None,
-            values: HashMap::new(),
-        }
+        Self::default()
     }
-    pub fn get(&mut self, token: &Token) -> Object {
+    pub fn get(&mut self, token: &Token) -> Result<Object, RuntimeError> {
         if self.values.contains_key(&token.lexeme) {
-            return self.values.get(&token.lexeme).unwrap().clone();
+            return Ok(self.values.get(&token.lexeme).unwrap().clone());
         }
 
         if let Some(enclosing) = &mut self.enclosing {
             return enclosing.get(token);
         }
-        todo!()
+        Err(RuntimeError::new(
+            token,
+            &format!("Undefined variable '{}'.", token.lexeme),
+        ))
     }
-    pub fn assign(&mut self, name: &String, object: Object) {
-        if self.values.contains_key(name) {
-            self.values.insert(name.clone(), object);
-            return;
+    pub fn assign(&mut self, token: &Token, object: Object) -> Result<(), RuntimeError> {
+        if self.values.contains_key(&token.lexeme) {
+            self.values.insert(token.lexeme.clone(), object);
+            return Ok(());
         }
         if let Some(enclosing) = &mut self.enclosing {
-            enclosing.assign(name, object);
-            return;
+            return enclosing.assign(token, object);
         }
-        todo!();
+        Err(RuntimeError::new(
+            token,
+            &format!("Undefined variable '{}'.", token.lexeme),
+        ))
     }
 
-    pub fn define(&mut self, name: &String, object: Object) {
-        self.values.insert(name.clone(), object);
+    /// Variables defined in this scope, sorted by name.
+    pub fn values(&self) -> Vec<(&String, &Object)> {
+        let mut values = self.values.iter().collect::<Vec<_>>();
+        values.sort_by(|a, b| a.0.cmp(b.0));
+        values
+    }
+
+    pub fn define(&mut self, name: &str, object: Object) {
+        self.values.insert(name.to_owned(), object);
+    }
+
+    /// Starts a scope nested in the current one, whose variables shadow the
+    /// outer ones until `end_scope`.
+    pub fn begin_scope(&mut self) {
+        let outer = std::mem::take(self);
+        self.enclosing = Some(Box::new(outer));
+    }
+
+    /// Drops the innermost scope and its variables.
+    pub fn end_scope(&mut self) {
+        if let Some(outer) = self.enclosing.take() {
+            *self = *outer;
+        }
     }
 }
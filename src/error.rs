@@ -1,10 +1,16 @@
-use std::io::Error;
+use std::{io::Error, rc::Rc};
 
-use crate::token::{Token, TokenType};
+use crate::token::{Object, Token, TokenType};
 
 #[derive(Default)]
 pub struct ScannerError {
     pub had_err: bool,
+
+    /// The source ended in the middle of a token, e.g. an open string.
+    pub incomplete: bool,
+
+    /// Record errors without printing them.
+    pub quiet: bool,
 }
 
 impl ScannerError {
@@ -12,12 +18,37 @@ impl ScannerError {
         self.report(line, "", message);
     }
 
+    /// Records an error from the scanner.
+    pub fn add(&mut self, e: &ScanError) {
+        self.incomplete |= e.incomplete;
+        self.send(e.line, &e.message);
+    }
+
     pub fn report(&mut self, line: usize, where_: &str, message: &str) {
-        println!("[line {}] Error {}: {}", line, where_, message);
+        if !self.quiet {
+            eprintln!("[line {}] Error{}: {}", line, where_, message);
+        }
         self.had_err = true;
     }
 }
 
+/// A malformed token, found at byte `at` of the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub line: usize,
+    pub at: usize,
+    pub message: String,
+
+    /// The source ended in the middle of the token.
+    pub incomplete: bool,
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[line {}] Error: {}", self.line, self.message)
+    }
+}
+
 pub struct ParseError {
     pub had_err: bool,
 }
@@ -26,19 +57,159 @@ impl ParseError {
     pub fn new(had_err: bool) -> Self {
         Self { had_err }
     }
-    pub fn send(&mut self, token: &Token, message: &str) -> Result<(), Error> {
+    pub fn send(&mut self, token: &Token, message: &str) -> Error {
         if token.token_type == TokenType::EOF {
             return self.report(token.line, " at end", message);
         }
         self.report(token.line, &format!(" at '{}'", token.lexeme), message)
     }
 
-    pub fn report(&mut self, line: usize, where_: &str, message: &str) -> Result<(), Error> {
-        println!("[line {}] Error {}: {}", line, where_, message);
+    pub fn report(&mut self, line: usize, where_: &str, message: &str) -> Error {
         self.had_err = true;
-        Err(Error::other(format!(
-            "[line {}] Error {}: {}",
-            line, where_, message
-        )))
+        Error::other(format!("[line {}] Error{}: {}", line, where_, message))
+    }
+}
+
+/// A call that was running, called from `line` of `file`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    pub file: Rc<str>,
+    pub line: usize,
+}
+
+impl Frame {
+    pub fn to_string(&self) -> String {
+        format!("{}() called at {}:{}", self.function, self.file, self.line)
+    }
+}
+
+/// The value a script sees for a caught runtime error, or one made with
+/// `Error(message)`.
+#[derive(Debug, PartialEq)]
+pub struct ErrorValue {
+    pub message: String,
+    pub line: usize,
+
+    /// Calls active when the error happened, innermost first.
+    pub stack: Vec<Frame>,
+}
+
+impl ErrorValue {
+    /// Fields readable with `error.name`.
+    pub const FIELDS: &'static [&'static str] = &["message", "line", "stack"];
+
+    pub fn field(&self, name: &str) -> Option<Object> {
+        match name {
+            "message" => Some(Object::Str(self.message.clone())),
+            "line" => Some(Object::Int(self.line as i64)),
+            "stack" => Some(Object::list(
+                self.stack
+                    .iter()
+                    .map(|frame| Object::Str(frame.to_string()))
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RuntimeError {
+    /// An operation failed at the given line.
+    Error {
+        line: usize,
+        message: String,
+
+        /// Filled in by the innermost call the error leaves.
+        stack: Vec<Frame>,
+    },
+
+    /// A value raised with `throw`.
+    Throw(Object, usize),
+
+    /// The host cancelled the script through its `Interrupt`.
+    Interrupted,
+
+    /// The script called `exit(code)`.
+    Exit(u8),
+
+    /// Unwinds to the enclosing loop, the parser only allows these inside one.
+    Break,
+    Continue,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: &str) -> Self {
+        Self::Error {
+            line: token.line,
+            message: message.to_owned(),
+            stack: vec![],
+        }
+    }
+
+    /// The value a `catch` clause binds, `None` for errors scripts cannot catch.
+    pub fn to_value(self) -> Option<Object> {
+        match self {
+            Self::Error {
+                line,
+                message,
+                stack,
+            } => Some(Object::Error(Rc::new(ErrorValue {
+                message,
+                line,
+                stack,
+            }))),
+            Self::Throw(value, _) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl RuntimeError {
+    /// Calls that were running when the error happened, innermost first.
+    pub fn stack(&self) -> &[Frame] {
+        match self {
+            Self::Error { stack, .. } => stack,
+            Self::Throw(Object::Error(error), _) => &error.stack,
+            _ => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_report(f, None)
+    }
+}
+
+impl RuntimeError {
+    /// The message, its location in `script` and the stack, as shown to the user.
+    pub fn report(&self, script: &str) -> String {
+        let mut report = String::new();
+        let _ = self.fmt_report(&mut report, Some(script));
+        report
+    }
+
+    fn fmt_report(&self, f: &mut impl std::fmt::Write, script: Option<&str>) -> std::fmt::Result {
+        let at = |line: usize| match script {
+            Some(script) => format!("[line {line} in {script}]"),
+            None => format!("[line {line}]"),
+        };
+        match self {
+            Self::Error { line, message, .. } => write!(f, "{message}\n{}", at(*line))?,
+            Self::Throw(Object::Error(error), _) => {
+                write!(f, "Uncaught Error: {}\n{}", error.message, at(error.line))?
+            }
+            Self::Throw(value, line) => write!(f, "Uncaught {}\n{}", value.repr(), at(*line))?,
+            Self::Interrupted => write!(f, "Interrupted.")?,
+            Self::Exit(code) => write!(f, "Exited with code {code}.")?,
+            Self::Break => write!(f, "'break' outside of a loop.")?,
+            Self::Continue => write!(f, "'continue' outside of a loop.")?,
+        }
+        for frame in self.stack() {
+            write!(f, "\n  in {}", frame.to_string())?;
+        }
+        Ok(())
     }
 }
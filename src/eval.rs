@@ -1,143 +1,449 @@
-use std::{rc::Rc, sync::Mutex};
+use std::{cmp::Ordering, rc::Rc, sync::Mutex};
 
 use crate::{
     environ::Environment,
+    error::{Frame, RuntimeError},
+    interrupt::Interrupt,
+    list,
+    map::Map,
+    native, num,
+    range::Range,
     stmt::Stmt,
-    token::{Object, TokenType},
+    time::{Clock, SystemClock},
+    token::{Object, Token, TokenType},
     Expr,
 };
 
 pub struct Evaluate {
     env: Rc<Mutex<Environment>>,
     stmts: Rc<Vec<Stmt>>,
+    interrupt: Option<Interrupt>,
+
+    /// Calls in progress, outermost first.
+    frames: Vec<Frame>,
+
+    /// Name of the script being run, for the frames.
+    script: Rc<str>,
+
+    /// The script may read and write files.
+    filesystem: bool,
+
+    /// Where the time functions get the time from.
+    clock: Rc<dyn Clock>,
 }
 
 impl Evaluate {
     pub fn new(stmts: Rc<Vec<Stmt>>, env: Rc<Mutex<Environment>>) -> Self {
-        Self { stmts, env }
+        Self {
+            stmts,
+            env,
+            interrupt: None,
+            frames: vec![],
+            script: "<script>".into(),
+            filesystem: true,
+            clock: Rc::new(SystemClock::new()),
+        }
+    }
+
+    pub fn with_script(mut self, script: &str) -> Self {
+        self.script = script.into();
+        self
+    }
+
+    pub fn with_interrupt(mut self, interrupt: Option<Interrupt>) -> Self {
+        self.interrupt = interrupt;
+        self
+    }
+
+    pub fn with_filesystem(mut self, allowed: bool) -> Self {
+        self.filesystem = allowed;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// False when the host keeps the script away from the filesystem.
+    pub fn filesystem_allowed(&self) -> bool {
+        self.filesystem
+    }
+
+    pub fn eval(&mut self) -> Result<(), RuntimeError> {
+        if let Some(interrupt) = &self.interrupt {
+            interrupt.start();
+        }
+        let result = self
+            .stmts
+            .clone()
+            .iter()
+            .try_for_each(|stmt| self.eval_stmt(stmt));
+        if let Some(interrupt) = &self.interrupt {
+            interrupt.finish();
+        }
+        result
+    }
+
+    /// The calls in progress, innermost first. Native functions given by an
+    /// embedder can use it to see where they were called from.
+    pub fn frames(&self) -> Vec<Frame> {
+        self.frames.iter().rev().cloned().collect()
     }
 
-    pub fn eval(&mut self) {
-        for stmt in self.stmts.clone().iter() {
-            self.eval_stmt(stmt);
+    /// One step of work, where the host gets a chance to cancel or pause us.
+    pub(crate) fn step(&self) -> Result<(), RuntimeError> {
+        match &self.interrupt {
+            Some(interrupt) => interrupt.check(),
+            None => Ok(()),
         }
     }
 
-    pub fn eval_expr(&mut self, expr: &Expr) -> Object {
+    pub fn eval_expr(&mut self, expr: &Expr) -> Result<Object, RuntimeError> {
         let v = match expr {
             Expr::Literal(v) => v.clone(),
             Expr::Binary(xl, t, xr) => {
-                let l_obj = self.eval_expr(xl.as_ref().unwrap());
-                let r_obj = self.eval_expr(xr.as_ref().unwrap());
+                let l_obj = self.eval_expr(xl.as_ref().unwrap())?;
+                let r_obj = self.eval_expr(xr.as_ref().unwrap())?;
 
                 match t.token_type {
-                    TokenType::GREATER => match (l_obj, r_obj) {
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l > r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l > r),
-                        _ => todo!(),
-                    },
-                    TokenType::GreaterEqual => match (l_obj, r_obj) {
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l >= r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l >= r),
-                        _ => todo!(),
-                    },
-                    TokenType::LESS => match (l_obj, r_obj) {
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l < r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l < r),
-                        _ => todo!(),
-                    },
-                    TokenType::LessEqual => match (l_obj, r_obj) {
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l <= r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l <= r),
-                        _ => todo!(),
-                    },
-                    TokenType::BangEqual => match (l_obj, r_obj) {
-                        (Object::Null, Object::Null) => Object::Bool(false),
-                        (Object::Null, _) => Object::Bool(true),
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l != r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l != r),
-                        _ => todo!(),
-                    },
-
-                    TokenType::EqualEqual => match (l_obj, r_obj) {
-                        (Object::Null, Object::Null) => Object::Bool(true),
-                        (Object::Null, _) => Object::Bool(false),
-                        (Object::Str(l), Object::Str(r)) => Object::Bool(l == r),
-                        (Object::Num(l), Object::Num(r)) => Object::Bool(l == r),
-                        _ => todo!(),
-                    },
-                    TokenType::MINUS => match (l_obj, r_obj) {
-                        (Object::Num(l), Object::Num(r)) => Object::Num(l - r),
-                        _ => todo!(),
-                    },
+                    TokenType::GREATER
+                    | TokenType::GreaterEqual
+                    | TokenType::LESS
+                    | TokenType::LessEqual => {
+                        let ordering = match (&l_obj, &r_obj) {
+                            (Object::Str(l), Object::Str(r)) => Some(l.cmp(r)),
+                            (l, r) if l.is_number() && r.is_number() => num::compare(l, r),
+                            _ => {
+                                return Err(RuntimeError::new(
+                                    t,
+                                    "Operands must be two numbers or two strings.",
+                                ))
+                            }
+                        };
+                        Object::Bool(match t.token_type {
+                            TokenType::GREATER => ordering == Some(Ordering::Greater),
+                            TokenType::GreaterEqual => {
+                                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                            }
+                            TokenType::LESS => ordering == Some(Ordering::Less),
+                            _ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                        })
+                    }
+                    TokenType::BangEqual => Object::Bool(l_obj != r_obj),
+                    TokenType::EqualEqual => Object::Bool(l_obj == r_obj),
 
                     TokenType::PLUS => match (l_obj, r_obj) {
-                        (Object::Num(l), Object::Num(r)) => Object::Num(l + r),
+                        (l, r) if l.is_number() && r.is_number() => num::arithmetic(t, &l, &r)?,
                         (Object::Str(l), Object::Str(r)) => Object::Str(format!("{l}{r}")),
-                        _ => todo!(),
+                        _ => {
+                            return Err(RuntimeError::new(
+                                t,
+                                "Operands must be two numbers or two strings.",
+                            ))
+                        }
                     },
                     TokenType::STAR => match (l_obj, r_obj) {
-                        (Object::Num(l), Object::Num(r)) => Object::Num(l * r),
-                        (Object::Str(l), Object::Num(r)) => Object::Str(l.repeat(r as usize)),
-                        (Object::Num(l), Object::Str(r)) => Object::Str(r.repeat(l as usize)),
-                        _ => todo!(),
+                        (l, r) if l.is_number() && r.is_number() => num::arithmetic(t, &l, &r)?,
+                        (Object::Str(s), n) | (n, Object::Str(s)) if n.is_number() => {
+                            Self::repeat(t, &s, &n)?
+                        }
+                        _ => {
+                            return Err(RuntimeError::new(
+                                t,
+                                "Operands must be numbers, or a string and a number.",
+                            ))
+                        }
                     },
 
-                    TokenType::SLASH => match (l_obj, r_obj) {
-                        (Object::Num(l), Object::Num(r)) => Object::Num(l / r),
-                        _ => todo!(),
+                    TokenType::MINUS
+                    | TokenType::SLASH
+                    | TokenType::PERCENT
+                    | TokenType::StarStar => match (l_obj, r_obj) {
+                        (l, r) if l.is_number() && r.is_number() => num::arithmetic(t, &l, &r)?,
+                        _ => return Err(RuntimeError::new(t, "Operands must be numbers.")),
                     },
 
-                    _ => todo!(),
+                    TokenType::DotDot | TokenType::DotDotEqual => Object::Range(Range::new(
+                        t,
+                        &l_obj,
+                        &r_obj,
+                        t.token_type == TokenType::DotDotEqual,
+                    )?),
+                    TokenType::IN => Object::Bool(Self::contains(t, &r_obj, &l_obj)?),
+
+                    _ => unreachable!(),
                 }
             }
 
             Expr::Unary(t, x) => {
-                let obj = self.eval_expr(x.as_ref().unwrap());
+                let obj = self.eval_expr(x.as_ref().unwrap())?;
                 match t.token_type {
-                    TokenType::MINUS => match obj {
-                        Object::Num(n) => Object::Num(-n),
-                        _ => todo!(),
-                    },
-                    TokenType::BANG => match obj {
-                        o @ _ => Object::Bool(!o.is_true()),
+                    TokenType::MINUS if obj.is_number() => num::negate(t, &obj)?,
+                    TokenType::MINUS => {
+                        return Err(RuntimeError::new(t, "Operand must be a number."))
+                    }
+                    TokenType::BANG => Object::Bool(!obj.is_true()),
+                    _ => unreachable!(),
+                }
+            }
+            Expr::Grouping(x) => self.eval_expr(x.as_ref().unwrap())?,
+            Expr::Call(callee, paren, args) => {
+                let callee = self.eval_expr(callee.as_ref().unwrap())?;
+                let mut values = vec![];
+                for arg in args {
+                    values.push(self.eval_expr(arg.as_ref().unwrap())?);
+                }
+                self.call(paren, callee, values)?
+            }
+            Expr::Get(object, name) => {
+                let object = self.eval_expr(object.as_ref().unwrap())?;
+                self.get(name, &object, &name.lexeme)?
+            }
+            Expr::Interpolate(parts) => {
+                let mut s = String::new();
+                for part in parts {
+                    s.push_str(&self.eval_expr(part.as_ref().unwrap())?.to_string());
+                }
+                Object::Str(s)
+            }
+            Expr::List(items) => {
+                let mut values = vec![];
+                for item in items {
+                    values.push(self.eval_expr(item.as_ref().unwrap())?);
+                }
+                Object::list(values)
+            }
+            Expr::Map(brace, entries) => {
+                let mut map = Map::default();
+                for (key, value) in entries {
+                    let key = self.eval_expr(key.as_ref().unwrap())?;
+                    let value = self.eval_expr(value.as_ref().unwrap())?;
+                    map.insert(brace, key, value)?;
+                }
+                Object::map(map)
+            }
+            Expr::Index(object, bracket, index) => {
+                let object = self.eval_expr(object.as_ref().unwrap())?;
+                let index = self.eval_expr(index.as_ref().unwrap())?;
+                match (object, index) {
+                    (Object::List(items), Object::Range(range)) => {
+                        let items = items.borrow();
+                        let positions = range.positions(bracket, items.len())?;
+                        Object::list(positions.into_iter().map(|at| items[at].clone()).collect())
+                    }
+                    (Object::Str(s), Object::Range(range)) => {
+                        let chars: Vec<char> = s.chars().collect();
+                        let positions = range.positions(bracket, chars.len())?;
+                        Object::Str(positions.into_iter().map(|at| chars[at]).collect())
+                    }
+                    (Object::List(items), index) => {
+                        let items = items.borrow();
+                        items[list::index(bracket, &index, items.len())?].clone()
+                    }
+                    (Object::Map(map), index) => match map.borrow().get(bracket, &index)? {
+                        Some(value) => value.clone(),
+                        None => {
+                            return Err(RuntimeError::new(
+                                bracket,
+                                &format!("Key {} not found.", index.repr()),
+                            ))
+                        }
                     },
-                    _ => todo!(),
+                    (Object::Str(_), _) => {
+                        return Err(RuntimeError::new(
+                            bracket,
+                            "Strings are sliced with a range.",
+                        ))
+                    }
+                    _ => {
+                        return Err(RuntimeError::new(
+                            bracket,
+                            "Only lists and maps can be indexed.",
+                        ))
+                    }
+                }
+            }
+            Expr::SetIndex(object, bracket, index, value) => {
+                let object = self.eval_expr(object.as_ref().unwrap())?;
+                let index = self.eval_expr(index.as_ref().unwrap())?;
+                let value = self.eval_expr(value.as_ref().unwrap())?;
+                match object {
+                    Object::List(items) => {
+                        let mut items = items.borrow_mut();
+                        let at = list::index(bracket, &index, items.len())?;
+                        items[at] = value.clone();
+                    }
+                    Object::Map(map) => map.borrow_mut().insert(bracket, index, value.clone())?,
+                    _ => {
+                        return Err(RuntimeError::new(
+                            bracket,
+                            "Only lists and maps can be indexed.",
+                        ))
+                    }
                 }
+                value
             }
-            Expr::Grouping(x) => self.eval_expr(x.as_ref().unwrap()),
-            Expr::Variable(v) => self.env.lock().unwrap().get(v),
+            Expr::Variable(v) => self.env.lock().unwrap().get(v)?,
             Expr::Assign(t, x) => {
-                let obj = self.eval_expr(x.as_ref().unwrap());
-                self.env.lock().unwrap().assign(&t.lexeme, obj.clone());
+                let obj = self.eval_expr(x.as_ref().unwrap())?;
+                self.env.lock().unwrap().assign(t, obj.clone())?;
                 obj
             }
             Expr::Logical(xl, t, xr) => {
-                let left = self.eval_expr(xl.as_ref().unwrap());
-                if t.token_type == TokenType::OR {
-                    if left.is_true() || !left.is_true() {
-                        return left;
-                    }
+                let left = self.eval_expr(xl.as_ref().unwrap())?;
+                let short_circuit = match t.token_type {
+                    TokenType::OR => left.is_true(),
+                    _ => !left.is_true(),
                 };
-                self.eval_expr(xr.as_ref().unwrap())
+                if short_circuit {
+                    return Ok(left);
+                }
+                self.eval_expr(xr.as_ref().unwrap())?
             }
         };
-        v
+        Ok(v)
+    }
+
+    fn call(
+        &mut self,
+        paren: &Token,
+        callee: Object,
+        mut args: Vec<Object>,
+    ) -> Result<Object, RuntimeError> {
+        let arg_count = args.len();
+        let native = match callee {
+            Object::Native(native) => native,
+            Object::Method(receiver, native) => {
+                args.insert(0, *receiver);
+                native
+            }
+            _ => return Err(RuntimeError::new(paren, "Can only call functions.")),
+        };
+        if native.arity.is_some_and(|arity| arity != arg_count) {
+            return Err(RuntimeError::new(
+                paren,
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    native.arity.unwrap(),
+                    arg_count
+                ),
+            ));
+        }
+        self.step()?;
+        self.frames.push(Frame {
+            function: native.name.to_owned(),
+            file: self.script.clone(),
+            line: paren.line,
+        });
+        let mut result = (native.func)(self, paren, args);
+        if let Err(RuntimeError::Error { stack, .. }) = &mut result {
+            if stack.is_empty() {
+                *stack = self.frames();
+            }
+        }
+        self.frames.pop();
+        result
+    }
+
+    /// Looks up the property `name` of `object`.
+    fn get(&mut self, token: &Token, object: &Object, name: &str) -> Result<Object, RuntimeError> {
+        if let Some(field) = match object {
+            Object::Error(error) => error.field(name),
+            Object::Module(module) => module.get(name),
+            _ => None,
+        } {
+            return Ok(field);
+        }
+        native::method(object, name).ok_or_else(|| {
+            RuntimeError::new(
+                token,
+                &format!("{} has no property '{}'.", object.type_name(), name),
+            )
+        })
+    }
+
+    /// `s * n`, the string repeated `n` times.
+    pub fn repeat(token: &Token, s: &str, n: &Object) -> Result<Object, RuntimeError> {
+        match n {
+            Object::Int(n) if *n >= 0 => Ok(Object::Str(s.repeat(*n as usize))),
+            _ => Err(RuntimeError::new(
+                token,
+                "A string can only be repeated a non-negative integer number of times.",
+            )),
+        }
+    }
+
+    /// `item in container`.
+    fn contains(token: &Token, container: &Object, item: &Object) -> Result<bool, RuntimeError> {
+        match (container, item) {
+            (Object::List(items), item) => Ok(items.borrow().contains(item)),
+            (Object::Map(map), key) => Ok(map.borrow().get(token, key)?.is_some()),
+            (Object::Range(range), Object::Int(n)) => Ok(range.contains(*n)),
+            (Object::Range(range), Object::Num(n)) => {
+                Ok(num::to_int(*n).is_some_and(|n| range.contains(n)))
+            }
+            (Object::Range(_), _) => Ok(false),
+            (Object::Str(s), Object::Str(part)) => Ok(s.contains(part.as_str())),
+            (Object::Str(_), _) => Err(RuntimeError::new(
+                token,
+                "Only a string can be looked for in a string.",
+            )),
+            (other, _) => Err(RuntimeError::new(
+                token,
+                &format!("Cannot look for a value in {}.", other.type_name()),
+            )),
+        }
+    }
+
+    /// Calls `object.name()` with no arguments.
+    fn call_method(
+        &mut self,
+        token: &Token,
+        object: &Object,
+        name: &str,
+    ) -> Result<Object, RuntimeError> {
+        let method = self.get(token, object, name)?;
+        self.call(token, method, vec![])
+    }
+
+    /// Runs `f` in a new scope, which is dropped however `f` ends.
+    fn in_scope(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), RuntimeError>,
+    ) -> Result<(), RuntimeError> {
+        self.env.lock().unwrap().begin_scope();
+        let result = f(self);
+        self.env.lock().unwrap().end_scope();
+        result
+    }
+
+    /// Runs one iteration of a loop, returning false if it hit a `break`.
+    fn eval_loop_body(&mut self, body: &Stmt) -> Result<bool, RuntimeError> {
+        match self.eval_stmt(body) {
+            Ok(()) | Err(RuntimeError::Continue) => Ok(true),
+            Err(RuntimeError::Break) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 
-    fn eval_stmt(&mut self, stmt: &Stmt) {
+    fn eval_stmt(&mut self, stmt: &Stmt) -> Result<(), RuntimeError> {
         match stmt {
             Stmt::PrintStmt(x) => {
-                let value = self.eval_expr(x.as_ref().unwrap());
+                let value = self.eval_expr(x.as_ref().unwrap())?;
                 println!("{}", value.to_string())
             }
 
             Stmt::ExprStmt(x) => {
-                self.eval_expr(x.as_ref().unwrap());
+                self.eval_expr(x.as_ref().unwrap())?;
             }
             Stmt::VarStmt(t, x) => {
                 let obj = match x.as_ref() {
-                    Some(v) => self.eval_expr(v.as_ref().unwrap()),
+                    Some(v) => self.eval_expr(v.as_ref().unwrap())?,
                     _ => Object::Null,
                 };
                 self.env.lock().unwrap().define(&t.lexeme, obj);
@@ -145,25 +451,84 @@ impl Evaluate {
             Stmt::BlockStmt(stmts) => {
                 let prev_env = self.env.clone();
                 self.env = self.env.clone();
-                for stmt in stmts {
-                    self.eval_stmt(&stmt);
-                }
+                let result = stmts.iter().try_for_each(|stmt| self.eval_stmt(stmt));
                 self.env = prev_env;
+                result?;
             }
             Stmt::IfStmt(cond, then, els) => {
-                let is_cond = self.eval_expr(cond.as_ref().unwrap()).is_true();
+                let is_cond = self.eval_expr(cond.as_ref().unwrap())?.is_true();
                 if is_cond {
-                    self.eval_stmt(then.as_ref());
-                } else if els.is_some() {
-                    self.eval_stmt(els.as_ref().unwrap().as_ref());
+                    self.eval_stmt(then.as_ref())?;
+                } else if let Some(els) = els {
+                    self.eval_stmt(els.as_ref())?;
                 }
             }
 
-            Stmt::WhileStmt(cond, body) => {
-                while self.eval_expr(cond.as_ref().unwrap()).is_true() {
-                    self.eval_stmt(&body);
+            Stmt::WhileStmt(cond, body, increment) => {
+                while self.eval_expr(cond.as_ref().unwrap())?.is_true() {
+                    self.step()?;
+                    if !self.eval_loop_body(body)? {
+                        break;
+                    }
+                    if let Some(increment) = increment {
+                        self.eval_expr(increment.as_ref().unwrap())?;
+                    }
+                }
+            }
+            Stmt::ThrowStmt(keyword, value) => {
+                let value = self.eval_expr(value.as_ref().unwrap())?;
+                return Err(RuntimeError::Throw(value, keyword.line));
+            }
+            Stmt::TryStmt(body, catch, finally) => {
+                let mut result = self.eval_stmt(body);
+                if let Some((name, handler)) = catch {
+                    result = match result {
+                        Err(e @ (RuntimeError::Error { .. } | RuntimeError::Throw(..))) => {
+                            let value = e.to_value().unwrap();
+                            self.in_scope(|eval| {
+                                eval.env.lock().unwrap().define(&name.lexeme, value);
+                                eval.eval_stmt(handler)
+                            })
+                        }
+                        result => result,
+                    };
                 }
+                // A host cancelling or the script exiting skips `finally`.
+                if let Some(finally) = finally {
+                    if !matches!(
+                        result,
+                        Err(RuntimeError::Interrupted | RuntimeError::Exit(_))
+                    ) {
+                        self.eval_stmt(finally)?;
+                    }
+                }
+                result?;
+            }
+            Stmt::BreakStmt(_) => return Err(RuntimeError::Break),
+            Stmt::ContinueStmt(_) => return Err(RuntimeError::Continue),
+            Stmt::ForInStmt(name, iterable, body) => {
+                let iterable = self.eval_expr(iterable.as_ref().unwrap())?;
+                let Ok(iter) = self.get(name, &iterable, "iter") else {
+                    return Err(RuntimeError::new(
+                        name,
+                        &format!("{} is not iterable.", iterable.type_name()),
+                    ));
+                };
+                let iter = self.call(name, iter, vec![])?;
+                // The loop variable lives in a scope of its own.
+                self.in_scope(|eval| {
+                    while eval.call_method(name, &iter, "has_next")?.is_true() {
+                        eval.step()?;
+                        let item = eval.call_method(name, &iter, "next")?;
+                        eval.env.lock().unwrap().define(&name.lexeme, item);
+                        if !eval.eval_loop_body(body)? {
+                            break;
+                        }
+                    }
+                    Ok(())
+                })?;
             }
         }
+        Ok(())
     }
 }
@@ -2,19 +2,39 @@ use std::io::Error;
 
 use crate::token::{Object, Token, TokenType};
 
+/// A `key: value` pair of a map literal.
+pub type MapEntry = (Result<Box<Expr>, Error>, Result<Box<Expr>, Error>);
+
 #[derive(Debug)]
 pub enum Expr {
     Assign(Token, Result<Box<Expr>, Error>),
     Binary(Result<Box<Expr>, Error>, Token, Result<Box<Expr>, Error>),
+    Call(
+        Result<Box<Expr>, Error>,
+        Token,
+        Vec<Result<Box<Expr>, Error>>,
+    ),
+    Get(Result<Box<Expr>, Error>, Token),
     Grouping(Result<Box<Expr>, Error>),
+    Index(Result<Box<Expr>, Error>, Token, Result<Box<Expr>, Error>),
+    /// A string with `${expr}` parts, joined with `Object::to_string`.
+    Interpolate(Vec<Result<Box<Expr>, Error>>),
+    List(Vec<Result<Box<Expr>, Error>>),
     Literal(Object),
     Logical(Result<Box<Expr>, Error>, Token, Result<Box<Expr>, Error>),
+    Map(Token, Vec<MapEntry>),
+    SetIndex(
+        Result<Box<Expr>, Error>,
+        Token,
+        Result<Box<Expr>, Error>,
+        Result<Box<Expr>, Error>,
+    ),
     Unary(Token, Result<Box<Expr>, Error>),
     Variable(Token),
 }
 
 impl Expr {
-    fn to_string(&self) -> String {
+    pub fn to_string(&self) -> String {
         let xs = match self {
             Self::Binary(xl, t, xr) => {
                 let op = match t.token_type {
@@ -22,7 +42,7 @@ impl Expr {
                     TokenType::SLASH => "/",
                     TokenType::PLUS => "+",
                     TokenType::MINUS => "-",
-                    _ => "??",
+                    _ => &t.lexeme,
                 };
                 if xl.is_err() {
                     return xl.as_ref().err().unwrap().to_string();
@@ -51,7 +71,86 @@ impl Expr {
                 }
                 format!("({} {})", t.lexeme, x.as_ref().unwrap().to_string())
             }
-            _ => todo!(),
+            Self::Logical(xl, t, xr) => {
+                if xl.is_err() {
+                    return xl.as_ref().err().unwrap().to_string();
+                }
+                if xr.is_err() {
+                    return xr.as_ref().err().unwrap().to_string();
+                }
+                format!(
+                    "({} {} {})",
+                    t.lexeme,
+                    xl.as_ref().unwrap().to_string(),
+                    xr.as_ref().unwrap().to_string()
+                )
+            }
+            Self::Variable(t) => t.lexeme.clone(),
+            Self::Call(callee, _, args) => {
+                let mut parts = vec![];
+                for x in std::iter::once(callee).chain(args) {
+                    match x {
+                        Ok(x) => parts.push(x.to_string()),
+                        Err(e) => return e.to_string(),
+                    }
+                }
+                format!("(call {})", parts.join(" "))
+            }
+            Self::Interpolate(items) => {
+                let mut parts = vec![];
+                for x in items {
+                    match x {
+                        Ok(x) => parts.push(x.to_string()),
+                        Err(e) => return e.to_string(),
+                    }
+                }
+                format!("(str {})", parts.join(" "))
+            }
+            Self::List(items) => {
+                let mut parts = vec![];
+                for x in items {
+                    match x {
+                        Ok(x) => parts.push(x.to_string()),
+                        Err(e) => return e.to_string(),
+                    }
+                }
+                format!("(list {})", parts.join(" "))
+            }
+            Self::Map(_, entries) => {
+                let mut parts = vec![];
+                for entry in entries {
+                    match entry {
+                        (Ok(k), Ok(v)) => {
+                            parts.push(format!("{} {}", k.to_string(), v.to_string()))
+                        }
+                        (Err(e), _) | (_, Err(e)) => return e.to_string(),
+                    }
+                }
+                format!("(map {})", parts.join(" "))
+            }
+            Self::Get(x, name) => match x {
+                Ok(x) => format!("(. {} {})", x.to_string(), name.lexeme),
+                Err(e) => e.to_string(),
+            },
+            Self::Index(x, _, i) => match (x, i) {
+                (Ok(x), Ok(i)) => format!("([] {} {})", x.to_string(), i.to_string()),
+                (Err(e), _) | (_, Err(e)) => e.to_string(),
+            },
+            Self::SetIndex(x, _, i, v) => match (x, i, v) {
+                (Ok(x), Ok(i), Ok(v)) => format!(
+                    "([]= {} {} {})",
+                    x.to_string(),
+                    i.to_string(),
+                    v.to_string()
+                ),
+                (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => e.to_string(),
+            },
+            Self::Assign(t, x) => {
+                if x.is_err() {
+                    return x.as_ref().err().unwrap().to_string();
+                }
+                format!("(= {} {})", t.lexeme, x.as_ref().unwrap().to_string())
+            }
         };
         xs
     }
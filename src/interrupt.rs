@@ -0,0 +1,121 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex,
+};
+
+use crate::error::RuntimeError;
+
+/// Handle shared between a host and a running script.
+///
+/// The evaluator checks it on every loop iteration and call. The host can
+/// cancel the script from another thread, or give it a budget of steps after
+/// which it pauses (keeping its whole state) until `resume` is called again.
+///
+/// A cancel and a budget last until `reset`, so they also apply to scripts
+/// run later with the same handle. Call `reset` before reusing it.
+#[derive(Clone, Default)]
+pub struct Interrupt {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancel: AtomicBool,
+    slice: Mutex<Slice>,
+    wake: Condvar,
+}
+
+#[derive(Default)]
+struct Slice {
+    /// Steps left before pausing, `None` runs without limit.
+    budget: Option<u64>,
+    paused: bool,
+    finished: bool,
+}
+
+impl Interrupt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the script to stop at the next check, even while paused.
+    pub fn cancel(&self) {
+        let _slice = self.inner.slice.lock().unwrap();
+        self.inner.cancel.store(true, Ordering::SeqCst);
+        self.inner.wake.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancel.load(Ordering::SeqCst)
+    }
+
+    /// Lets the script run `steps` more steps, then pause again.
+    pub fn resume(&self, steps: u64) {
+        let mut slice = self.inner.slice.lock().unwrap();
+        slice.budget = Some(steps);
+        slice.paused = false;
+        self.inner.wake.notify_all();
+    }
+
+    /// Removes the step budget, the script runs until it ends or is cancelled.
+    pub fn unlimit(&self) {
+        let mut slice = self.inner.slice.lock().unwrap();
+        slice.budget = None;
+        slice.paused = false;
+        self.inner.wake.notify_all();
+    }
+
+    /// Clears a cancel, the step budget and whether the last script
+    /// finished, so the handle can be used for another run. Call it between
+    /// runs, not while a script is running.
+    pub fn reset(&self) {
+        let mut slice = self.inner.slice.lock().unwrap();
+        self.inner.cancel.store(false, Ordering::SeqCst);
+        *slice = Slice::default();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.slice.lock().unwrap().paused
+    }
+
+    /// Blocks until the script pauses (`true`) or finishes (`false`).
+    pub fn wait_paused(&self) -> bool {
+        let mut slice = self.inner.slice.lock().unwrap();
+        while !slice.paused && !slice.finished {
+            slice = self.inner.wake.wait(slice).unwrap();
+        }
+        slice.paused
+    }
+
+    pub(crate) fn start(&self) {
+        self.inner.slice.lock().unwrap().finished = false;
+    }
+
+    pub(crate) fn finish(&self) {
+        self.inner.slice.lock().unwrap().finished = true;
+        self.inner.wake.notify_all();
+    }
+
+    /// Called by the evaluator once per step.
+    pub(crate) fn check(&self) -> Result<(), RuntimeError> {
+        if self.is_cancelled() {
+            return Err(RuntimeError::Interrupted);
+        }
+        let mut slice = self.inner.slice.lock().unwrap();
+        if slice.budget == Some(0) {
+            slice.paused = true;
+            self.inner.wake.notify_all();
+            while slice.budget == Some(0) && !self.is_cancelled() {
+                slice = self.inner.wake.wait(slice).unwrap();
+            }
+            if self.is_cancelled() {
+                slice.paused = false;
+                return Err(RuntimeError::Interrupted);
+            }
+        }
+        if let Some(budget) = slice.budget.as_mut() {
+            *budget -= 1;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    native::NativeFn,
+    token::{Object, Token},
+};
+
+/// Global functions that read and write files and stdin. Failures are
+/// runtime errors a script can catch.
+pub const FUNCTIONS: &[NativeFn] = &[
+    NativeFn {
+        name: "read_file",
+        arity: Some(1),
+        func: read_file,
+    },
+    NativeFn {
+        name: "write_file",
+        arity: Some(2),
+        func: write_file,
+    },
+    NativeFn {
+        name: "append_file",
+        arity: Some(2),
+        func: append_file,
+    },
+    NativeFn {
+        name: "read_lines",
+        arity: Some(1),
+        func: read_lines,
+    },
+    NativeFn {
+        name: "exists",
+        arity: Some(1),
+        func: exists,
+    },
+    NativeFn {
+        name: "list_dir",
+        arity: Some(1),
+        func: list_dir,
+    },
+    NativeFn {
+        name: "input",
+        arity: Some(1),
+        func: input,
+    },
+    NativeFn {
+        name: "read_line",
+        arity: Some(0),
+        func: read_line,
+    },
+];
+
+/// The path argument, if the host allows the script to use the filesystem.
+fn path<'a>(eval: &Evaluate, paren: &Token, args: &'a [Object]) -> Result<&'a str, RuntimeError> {
+    if !eval.filesystem_allowed() {
+        return Err(RuntimeError::new(paren, "Filesystem access is disabled."));
+    }
+    match &args[0] {
+        Object::Str(path) => Ok(path),
+        other => Err(RuntimeError::new(
+            paren,
+            &format!("Path must be a string, not {}.", other.type_name()),
+        )),
+    }
+}
+
+fn failed(paren: &Token, action: &str, path: &str, e: io::Error) -> RuntimeError {
+    RuntimeError::new(paren, &format!("Cannot {action} '{path}': {e}."))
+}
+
+fn read_file(
+    eval: &mut Evaluate,
+    paren: &Token,
+    args: Vec<Object>,
+) -> Result<Object, RuntimeError> {
+    let path = path(eval, paren, &args)?;
+    fs::read_to_string(path)
+        .map(Object::Str)
+        .map_err(|e| failed(paren, "read", path, e))
+}
+
+/// `write_file(path, value)` replaces the file with the value as `print` shows it.
+fn write_file(
+    eval: &mut Evaluate,
+    paren: &Token,
+    args: Vec<Object>,
+) -> Result<Object, RuntimeError> {
+    let path = path(eval, paren, &args)?;
+    fs::write(path, args[1].to_string()).map_err(|e| failed(paren, "write", path, e))?;
+    Ok(Object::Null)
+}
+
+fn append_file(
+    eval: &mut Evaluate,
+    paren: &Token,
+    args: Vec<Object>,
+) -> Result<Object, RuntimeError> {
+    let path = path(eval, paren, &args)?;
+    fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(args[1].to_string().as_bytes()))
+        .map_err(|e| failed(paren, "append to", path, e))?;
+    Ok(Object::Null)
+}
+
+/// `read_lines(path)`, the lines of a file without their line breaks.
+fn read_lines(
+    eval: &mut Evaluate,
+    paren: &Token,
+    args: Vec<Object>,
+) -> Result<Object, RuntimeError> {
+    let path = path(eval, paren, &args)?;
+    let text = fs::read_to_string(path).map_err(|e| failed(paren, "read", path, e))?;
+    Ok(Object::list(
+        text.lines().map(|line| Object::Str(line.into())).collect(),
+    ))
+}
+
+fn exists(eval: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(Path::new(path(eval, paren, &args)?).exists()))
+}
+
+/// `list_dir(path)`, the names of the entries of a directory, sorted.
+fn list_dir(eval: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let path = path(eval, paren, &args)?;
+    let mut names = fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+                .collect::<io::Result<Vec<_>>>()
+        })
+        .map_err(|e| failed(paren, "list", path, e))?;
+    names.sort();
+    Ok(Object::list(names.into_iter().map(Object::Str).collect()))
+}
+
+/// `input(prompt)` prints the prompt and reads a line like `read_line`.
+fn input(eval: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    print!("{}", args[0].to_string());
+    io::stdout()
+        .flush()
+        .map_err(|e| RuntimeError::new(paren, &format!("Cannot write the prompt: {e}.")))?;
+    read_line(eval, paren, args)
+}
+
+/// `read_line()`, the next line of stdin without its line break, or null at
+/// the end of the input.
+fn read_line(_: &mut Evaluate, paren: &Token, _: Vec<Object>) -> Result<Object, RuntimeError> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| RuntimeError::new(paren, &format!("Cannot read stdin: {e}.")))?;
+    if read == 0 {
+        return Ok(Object::Null);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Object::Str(line))
+}
@@ -0,0 +1,114 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    native::NativeFn,
+    range::Range,
+    token::{Object, Token},
+};
+
+/// State of a built-in iterator.
+///
+/// `for (var x in value)` asks `value.iter()` for an iterator, then calls
+/// `has_next()` and `next()` on it until `has_next()` is false. Any value
+/// with those methods can be looped over.
+#[derive(Debug, PartialEq)]
+pub enum Iter {
+    /// Walks a live list, so items pushed during the loop are visited too.
+    List(Rc<RefCell<Vec<Object>>>, usize),
+
+    /// Walks a snapshot, e.g. the keys of a map or characters of a string.
+    Items(Vec<Object>, usize),
+
+    /// Counts through a range without building a list.
+    Range(Range, usize),
+}
+
+impl Iter {
+    pub fn has_next(&self) -> bool {
+        match self {
+            Self::List(items, at) => *at < items.borrow().len(),
+            Self::Items(items, at) => *at < items.len(),
+            Self::Range(range, at) => (*at as i128) < range.len(),
+        }
+    }
+}
+
+impl Iterator for Iter {
+    type Item = Object;
+
+    fn next(&mut self) -> Option<Object> {
+        let item = match self {
+            Self::List(items, at) => items.borrow().get(*at).cloned(),
+            Self::Items(items, at) => items.get(*at).cloned(),
+            Self::Range(range, at) => {
+                let at = *at as i128;
+                (at < range.len()).then(|| Object::Int(range.get(at)))
+            }
+        }?;
+        match self {
+            Self::List(_, at) | Self::Items(_, at) | Self::Range(_, at) => *at += 1,
+        }
+        Some(item)
+    }
+}
+
+/// The `iter` method shared by every built-in iterable.
+pub const ITER: NativeFn = NativeFn {
+    name: "iter",
+    arity: Some(0),
+    func: iter,
+};
+
+/// Methods of iterator values.
+pub const METHODS: &[NativeFn] = &[
+    ITER,
+    NativeFn {
+        name: "has_next",
+        arity: Some(0),
+        func: has_next,
+    },
+    NativeFn {
+        name: "next",
+        arity: Some(0),
+        func: next,
+    },
+];
+
+fn iter(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let iter = match &args[0] {
+        Object::List(items) => Iter::List(items.clone(), 0),
+        Object::Map(map) => Iter::Items(
+            map.borrow()
+                .entries()
+                .iter()
+                .map(|(k, _)| k.clone())
+                .collect(),
+            0,
+        ),
+        Object::Range(range) => Iter::Range(*range, 0),
+        Object::Str(s) => Iter::Items(s.chars().map(|c| Object::Str(c.into())).collect(), 0),
+        Object::Iterator(_) => return Ok(args[0].clone()),
+        other => unreachable!("iter called on {}", other.type_name()),
+    };
+    Ok(Object::iterator(iter))
+}
+
+fn iterator(args: &[Object]) -> Rc<RefCell<Iter>> {
+    match &args[0] {
+        Object::Iterator(iter) => iter.clone(),
+        _ => unreachable!("iterator method called on {}", args[0].type_name()),
+    }
+}
+
+fn has_next(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(iterator(&args).borrow().has_next()))
+}
+
+fn next(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    iterator(&args)
+        .borrow_mut()
+        .next()
+        .ok_or_else(|| RuntimeError::new(paren, "Iterator is exhausted."))
+}
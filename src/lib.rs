@@ -0,0 +1,37 @@
+// The token and statement enums follow the naming used in Crafting Interpreters.
+#![allow(
+    clippy::upper_case_acronyms,
+    clippy::enum_variant_names,
+    clippy::inherent_to_string
+)]
+
+#[cfg(feature = "bigint")]
+pub mod bigint;
+pub mod cli;
+pub mod completion;
+pub mod environ;
+pub mod error;
+pub mod eval;
+pub mod expr;
+pub mod interrupt;
+pub mod io;
+pub mod iter;
+pub mod list;
+pub mod map;
+pub mod math;
+pub mod mlox;
+pub mod native;
+pub mod num;
+pub mod parser;
+pub mod range;
+pub mod relex;
+pub mod scanner;
+pub mod stmt;
+pub mod string;
+pub mod time;
+pub mod token;
+
+pub use expr::*;
+pub use interrupt::Interrupt;
+pub use mlox::Mlox;
+pub use stmt::*;
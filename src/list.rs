@@ -0,0 +1,183 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    iter,
+    native::NativeFn,
+    num,
+    token::{Object, Token},
+};
+
+/// Methods of list values, the list itself is passed as the first argument.
+pub const METHODS: &[NativeFn] = &[
+    NativeFn {
+        name: "len",
+        arity: Some(0),
+        func: len,
+    },
+    NativeFn {
+        name: "push",
+        arity: Some(1),
+        func: push,
+    },
+    NativeFn {
+        name: "pop",
+        arity: Some(0),
+        func: pop,
+    },
+    NativeFn {
+        name: "insert",
+        arity: Some(2),
+        func: insert,
+    },
+    NativeFn {
+        name: "remove",
+        arity: Some(1),
+        func: remove,
+    },
+    NativeFn {
+        name: "slice",
+        arity: None,
+        func: slice,
+    },
+    NativeFn {
+        name: "sort",
+        arity: Some(0),
+        func: sort,
+    },
+    NativeFn {
+        name: "reverse",
+        arity: Some(0),
+        func: reverse,
+    },
+    NativeFn {
+        name: "contains",
+        arity: Some(1),
+        func: contains,
+    },
+    iter::ITER,
+];
+
+/// Turns a possibly negative index into a position in a list of `len` items.
+pub fn index(token: &Token, index: &Object, len: usize) -> Result<usize, RuntimeError> {
+    let position = position(token, index, len)?;
+    if position < 0 || position >= len as i64 {
+        return Err(RuntimeError::new(
+            token,
+            &format!(
+                "Index {} out of bounds for length {len}.",
+                index.to_string()
+            ),
+        ));
+    }
+    Ok(position as usize)
+}
+
+/// Like `index`, but clamps to `0..=len` instead of failing, as slices do.
+pub fn bound(token: &Token, index: &Object, len: usize) -> Result<usize, RuntimeError> {
+    Ok(position(token, index, len)?.clamp(0, len as i64) as usize)
+}
+
+fn position(token: &Token, index: &Object, len: usize) -> Result<i64, RuntimeError> {
+    match index {
+        Object::Int(n) if *n < 0 => Ok(len as i64 + n),
+        Object::Int(n) => Ok(*n),
+        _ => Err(RuntimeError::new(token, "Index must be an integer.")),
+    }
+}
+
+fn items(args: &[Object]) -> Rc<RefCell<Vec<Object>>> {
+    match &args[0] {
+        Object::List(items) => items.clone(),
+        _ => unreachable!("list method called on {}", args[0].type_name()),
+    }
+}
+
+fn len(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(items(&args).borrow().len() as i64))
+}
+
+fn push(_: &mut Evaluate, _: &Token, mut args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let value = args.pop().unwrap();
+    items(&args).borrow_mut().push(value);
+    Ok(Object::Null)
+}
+
+fn pop(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    items(&args)
+        .borrow_mut()
+        .pop()
+        .ok_or_else(|| RuntimeError::new(paren, "Cannot pop from an empty list."))
+}
+
+fn insert(_: &mut Evaluate, paren: &Token, mut args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let value = args.pop().unwrap();
+    let items = items(&args);
+    let len = items.borrow().len();
+    // Inserting right after the last item is allowed.
+    let at = match position(paren, &args[1], len)? {
+        at if at == len as i64 => len,
+        _ => index(paren, &args[1], len)?,
+    };
+    items.borrow_mut().insert(at, value);
+    Ok(Object::Null)
+}
+
+fn remove(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let items = items(&args);
+    let at = index(paren, &args[1], items.borrow().len())?;
+    let removed = items.borrow_mut().remove(at);
+    Ok(removed)
+}
+
+/// `xs.slice(start)` or `xs.slice(start, end)`, a new list.
+fn slice(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    if !(2..=3).contains(&args.len()) {
+        return Err(RuntimeError::new(
+            paren,
+            &format!("Expected 1 or 2 arguments but got {}.", args.len() - 1),
+        ));
+    }
+    let items = items(&args);
+    let items = items.borrow();
+    let start = bound(paren, &args[1], items.len())?;
+    let end = match args.get(2) {
+        Some(end) => bound(paren, end, items.len())?,
+        None => items.len(),
+    };
+    Ok(Object::list(
+        items
+            .get(start..end.max(start))
+            .unwrap_or_default()
+            .to_vec(),
+    ))
+}
+
+fn sort(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let items = items(&args);
+    let mut items = items.borrow_mut();
+    let all_nums = items.iter().all(Object::is_number);
+    let all_strs = items.iter().all(|item| matches!(item, Object::Str(_)));
+    if !all_nums && !all_strs {
+        return Err(RuntimeError::new(
+            paren,
+            "Can only sort a list of numbers or a list of strings.",
+        ));
+    }
+    items.sort_by(|a, b| match (a, b) {
+        (Object::Str(a), Object::Str(b)) => a.cmp(b),
+        (a, b) => num::compare(a, b)
+            .unwrap_or_else(|| a.as_f64().unwrap().total_cmp(&b.as_f64().unwrap())),
+    });
+    Ok(Object::Null)
+}
+
+fn reverse(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    items(&args).borrow_mut().reverse();
+    Ok(Object::Null)
+}
+
+fn contains(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(items(&args).borrow().contains(&args[1])))
+}
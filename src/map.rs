@@ -0,0 +1,190 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    iter,
+    native::NativeFn,
+    num,
+    token::{Object, Token},
+};
+
+/// Hashable form of the values allowed as map keys.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Str(String),
+    Int(i64),
+    #[cfg(feature = "bigint")]
+    BigInt(crate::bigint::BigInt),
+    Num(u64),
+    Bool(bool),
+}
+
+impl Key {
+    fn new(token: &Token, key: &Object) -> Result<Self, RuntimeError> {
+        match key {
+            Object::Str(s) => Ok(Self::Str(s.clone())),
+            Object::Int(n) => Ok(Self::Int(*n)),
+            #[cfg(feature = "bigint")]
+            Object::BigInt(n) => Ok(Self::BigInt((**n).clone())),
+            // A whole float is the same key as the integer, as they are equal.
+            // Past i64, it matches a BigInt under the feature.
+            Object::Num(n) => Ok(match num::to_int(*n) {
+                Some(n) => Self::Int(n),
+                #[cfg(feature = "bigint")]
+                None if n.fract() == 0.0 && n.is_finite() => {
+                    Self::BigInt(crate::bigint::BigInt::from_f64(*n).unwrap())
+                }
+                None => Self::Num(n.to_bits()),
+            }),
+            Object::Bool(b) => Ok(Self::Bool(*b)),
+            _ => Err(RuntimeError::new(
+                token,
+                "Map keys must be strings, numbers or booleans.",
+            )),
+        }
+    }
+}
+
+/// A hash map that remembers insertion order, so iterating and printing
+/// it gives the same result on every run.
+#[derive(Debug, Default)]
+pub struct Map {
+    entries: Vec<(Object, Object)>,
+    index: HashMap<Key, usize>,
+}
+
+impl Map {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[(Object, Object)] {
+        &self.entries
+    }
+
+    pub fn get(&self, token: &Token, key: &Object) -> Result<Option<&Object>, RuntimeError> {
+        let at = self.index.get(&Key::new(token, key)?);
+        Ok(at.map(|&at| &self.entries[at].1))
+    }
+
+    pub fn insert(
+        &mut self,
+        token: &Token,
+        key: Object,
+        value: Object,
+    ) -> Result<(), RuntimeError> {
+        match self.index.get(&Key::new(token, &key)?) {
+            Some(&at) => self.entries[at].1 = value,
+            None => {
+                self.index
+                    .insert(Key::new(token, &key)?, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, token: &Token, key: &Object) -> Result<Option<Object>, RuntimeError> {
+        let Some(at) = self.index.remove(&Key::new(token, key)?) else {
+            return Ok(None);
+        };
+        let (_, value) = self.entries.remove(at);
+        for i in self.index.values_mut() {
+            if *i > at {
+                *i -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// True if both maps have the same keys, with values equal by `eq`.
+    pub fn eq_by(&self, other: &Self, mut eq: impl FnMut(&Object, &Object) -> bool) -> bool {
+        self.len() == other.len()
+            && self.index.iter().all(|(key, &at)| {
+                other
+                    .index
+                    .get(key)
+                    .is_some_and(|&other_at| eq(&self.entries[at].1, &other.entries[other_at].1))
+            })
+    }
+}
+
+/// Methods of map values, the map itself is passed as the first argument.
+pub const METHODS: &[NativeFn] = &[
+    NativeFn {
+        name: "len",
+        arity: Some(0),
+        func: len,
+    },
+    NativeFn {
+        name: "keys",
+        arity: Some(0),
+        func: keys,
+    },
+    NativeFn {
+        name: "values",
+        arity: Some(0),
+        func: values,
+    },
+    NativeFn {
+        name: "has",
+        arity: Some(1),
+        func: has,
+    },
+    NativeFn {
+        name: "remove",
+        arity: Some(1),
+        func: remove,
+    },
+    iter::ITER,
+];
+
+fn map(args: &[Object]) -> Rc<RefCell<Map>> {
+    match &args[0] {
+        Object::Map(map) => map.clone(),
+        _ => unreachable!("map method called on {}", args[0].type_name()),
+    }
+}
+
+fn len(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(map(&args).borrow().len() as i64))
+}
+
+fn keys(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let map = map(&args);
+    let keys = map
+        .borrow()
+        .entries()
+        .iter()
+        .map(|(k, _)| k.clone())
+        .collect();
+    Ok(Object::list(keys))
+}
+
+fn values(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let map = map(&args);
+    let values = map
+        .borrow()
+        .entries()
+        .iter()
+        .map(|(_, v)| v.clone())
+        .collect();
+    Ok(Object::list(values))
+}
+
+fn has(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(
+        map(&args).borrow().get(paren, &args[1])?.is_some(),
+    ))
+}
+
+/// `m.remove(key)` deletes the entry and returns its value, or null if absent.
+fn remove(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let removed = map(&args).borrow_mut().remove(paren, &args[1])?;
+    Ok(removed.unwrap_or(Object::Null))
+}
@@ -0,0 +1,287 @@
+use std::{cmp::Ordering, f64::consts};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    native::{Module, NativeFn},
+    num,
+    token::{Object, Token},
+};
+
+/// Functions of the `math` module.
+const FUNCTIONS: &[NativeFn] = &[
+    NativeFn {
+        name: "sqrt",
+        arity: Some(1),
+        func: sqrt,
+    },
+    NativeFn {
+        name: "pow",
+        arity: Some(2),
+        func: pow,
+    },
+    NativeFn {
+        name: "abs",
+        arity: Some(1),
+        func: abs,
+    },
+    NativeFn {
+        name: "floor",
+        arity: Some(1),
+        func: floor,
+    },
+    NativeFn {
+        name: "ceil",
+        arity: Some(1),
+        func: ceil,
+    },
+    NativeFn {
+        name: "round",
+        arity: Some(1),
+        func: round,
+    },
+    NativeFn {
+        name: "min",
+        arity: None,
+        func: min,
+    },
+    NativeFn {
+        name: "max",
+        arity: None,
+        func: max,
+    },
+    NativeFn {
+        name: "sin",
+        arity: Some(1),
+        func: sin,
+    },
+    NativeFn {
+        name: "cos",
+        arity: Some(1),
+        func: cos,
+    },
+    NativeFn {
+        name: "tan",
+        arity: Some(1),
+        func: tan,
+    },
+    NativeFn {
+        name: "asin",
+        arity: Some(1),
+        func: asin,
+    },
+    NativeFn {
+        name: "acos",
+        arity: Some(1),
+        func: acos,
+    },
+    NativeFn {
+        name: "atan",
+        arity: Some(1),
+        func: atan,
+    },
+    NativeFn {
+        name: "atan2",
+        arity: Some(2),
+        func: atan2,
+    },
+    NativeFn {
+        name: "exp",
+        arity: Some(1),
+        func: exp,
+    },
+    NativeFn {
+        name: "log",
+        arity: None,
+        func: log,
+    },
+    NativeFn {
+        name: "log2",
+        arity: Some(1),
+        func: log2,
+    },
+    NativeFn {
+        name: "log10",
+        arity: Some(1),
+        func: log10,
+    },
+];
+
+const CONSTANTS: &[(&str, f64)] = &[
+    ("pi", consts::PI),
+    ("e", consts::E),
+    ("inf", f64::INFINITY),
+    ("nan", f64::NAN),
+];
+
+/// The `math` module.
+pub fn module() -> Module {
+    let functions = FUNCTIONS
+        .iter()
+        .map(|f| (f.name, Object::native(f.clone())));
+    let constants = CONSTANTS
+        .iter()
+        .map(|&(name, value)| (name, Object::Num(value)));
+    Module {
+        name: "math",
+        members: functions.chain(constants).collect(),
+    }
+}
+
+/// The argument at `at` as a float.
+fn number(paren: &Token, args: &[Object], at: usize) -> Result<f64, RuntimeError> {
+    args[at]
+        .as_f64()
+        .ok_or_else(|| not_a_number(paren, &args[at]))
+}
+
+fn not_a_number(paren: &Token, arg: &Object) -> RuntimeError {
+    RuntimeError::new(
+        paren,
+        &format!("Expect a number argument but got {}.", arg.type_name()),
+    )
+}
+
+fn float(paren: &Token, args: &[Object], f: fn(f64) -> f64) -> Result<Object, RuntimeError> {
+    Ok(Object::Num(f(number(paren, args, 0)?)))
+}
+
+/// Applies `f` to a float argument, an Int if the result fits in one.
+/// Integers are already whole and are returned as they are.
+fn to_whole(paren: &Token, args: &[Object], f: fn(f64) -> f64) -> Result<Object, RuntimeError> {
+    match &args[0] {
+        Object::Num(n) => {
+            let n = f(*n);
+            Ok(num::to_int(n).map_or(Object::Num(n), Object::Int))
+        }
+        n if n.is_number() => Ok(n.clone()),
+        other => Err(not_a_number(paren, other)),
+    }
+}
+
+fn sqrt(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::sqrt)
+}
+
+/// `math.pow(x, y)`, the same as `x ** y`.
+fn pow(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    for arg in &args {
+        if !arg.is_number() {
+            return Err(not_a_number(paren, arg));
+        }
+    }
+    num::power(paren, &args[0], &args[1])
+}
+
+fn abs(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    match &args[0] {
+        Object::Num(n) => Ok(Object::Num(n.abs())),
+        n if n.is_number() && num::compare(n, &Object::Int(0)) == Some(Ordering::Less) => {
+            num::negate(paren, n)
+        }
+        n if n.is_number() => Ok(n.clone()),
+        other => Err(not_a_number(paren, other)),
+    }
+}
+
+fn floor(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    to_whole(paren, &args, f64::floor)
+}
+
+fn ceil(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    to_whole(paren, &args, f64::ceil)
+}
+
+/// `math.round(x)`, halves round away from zero.
+fn round(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    to_whole(paren, &args, f64::round)
+}
+
+/// The smallest or largest of the arguments, or of the items of a single
+/// list argument, by `wanted`.
+fn extreme(paren: &Token, args: Vec<Object>, wanted: Ordering) -> Result<Object, RuntimeError> {
+    let items = match args.as_slice() {
+        [Object::List(items)] => items.borrow().clone(),
+        _ => args,
+    };
+    let mut items = items.into_iter();
+    let Some(mut best) = items.next() else {
+        return Err(RuntimeError::new(paren, "Expect at least one number."));
+    };
+    if !best.is_number() {
+        return Err(not_a_number(paren, &best));
+    }
+    for item in items {
+        if !item.is_number() {
+            return Err(not_a_number(paren, &item));
+        }
+        if num::compare(&item, &best) == Some(wanted) {
+            best = item;
+        }
+    }
+    Ok(best)
+}
+
+fn min(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    extreme(paren, args, Ordering::Less)
+}
+
+fn max(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    extreme(paren, args, Ordering::Greater)
+}
+
+fn sin(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::sin)
+}
+
+fn cos(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::cos)
+}
+
+fn tan(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::tan)
+}
+
+fn asin(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::asin)
+}
+
+fn acos(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::acos)
+}
+
+fn atan(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::atan)
+}
+
+/// `math.atan2(y, x)`, the angle of the point `(x, y)`.
+fn atan2(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let y = number(paren, &args, 0)?;
+    Ok(Object::Num(y.atan2(number(paren, &args, 1)?)))
+}
+
+fn exp(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::exp)
+}
+
+/// `math.log(x)`, the natural logarithm, or `math.log(x, base)`.
+fn log(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    match args.len() {
+        1 => float(paren, &args, f64::ln),
+        2 => Ok(Object::Num(
+            number(paren, &args, 0)?.log(number(paren, &args, 1)?),
+        )),
+        n => Err(RuntimeError::new(
+            paren,
+            &format!("Expected 1 or 2 arguments but got {n}."),
+        )),
+    }
+}
+
+fn log2(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::log2)
+}
+
+fn log10(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    float(paren, &args, f64::log10)
+}
@@ -1,71 +1,389 @@
 use crate::{
-    environ::Environment, error::ScannerError, eval::Evaluate, parser::Parser, scanner::Scanner,
+    cli::{Cli, Command, USAGE},
+    completion::MloxHelper,
+    environ::Environment,
+    error::{Frame, RuntimeError, ScannerError},
+    eval::Evaluate,
+    interrupt::Interrupt,
+    native::{self, NativeFn},
+    parser::Parser,
+    scanner::Scanner,
+    time::{Clock, SystemClock},
+    token::{Object, Token, TokenType},
+    Stmt,
 };
-use std::{io::Write, rc::Rc, sync::Mutex};
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use std::{io::Read, path::PathBuf, rc::Rc, sync::Mutex, time::Instant};
+
+/// Prompt history, kept in the user's home directory.
+const HISTORY_FILE: &str = ".mlox_history";
+
+const HELP: &str = "\
+:help           Show this message
+:env            List the global variables with their values and types
+:load <file>    Run a file in the current session
+:reset          Forget every variable
+:ast <expr>     Show the parsed tree of an expression
+:tokens <src>   Show the tokens scanned from the source
+:time <src>     Run the source and report how long it took";
+
+// Process exit codes, following sysexits.h.
+const EX_USAGE: u8 = 64;
+const EX_DATAERR: u8 = 65;
+const EX_NOINPUT: u8 = 66;
+const EX_SOFTWARE: u8 = 70;
+const EX_IOERR: u8 = 74;
+
+/// How running a piece of source ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ok,
+    CompileError,
+    RuntimeError,
+    Interrupted,
+
+    /// The script called `exit(code)`.
+    Exit(u8),
+}
+
+impl Status {
+    pub fn code(self) -> u8 {
+        match self {
+            Self::Ok => 0,
+            Self::CompileError => EX_DATAERR,
+            Self::RuntimeError => EX_SOFTWARE,
+            Self::Interrupted => 130,
+            Self::Exit(code) => code,
+        }
+    }
+}
 
 pub struct Mlox {
     args: Vec<String>,
     env: Rc<Mutex<Environment>>,
     error_handler: ScannerError,
+    interrupt: Option<Interrupt>,
+
+    /// Do not print the banners.
+    quiet: bool,
+
+    /// Name of the script being run, shown in stack traces.
+    script: String,
+
+    /// Calls that were running when the last run ended with an error.
+    last_trace: Vec<Frame>,
+
+    /// Scripts may read and write files.
+    filesystem: bool,
+
+    /// Where scripts get the time from.
+    clock: Rc<dyn Clock>,
+
+    /// Globals from the host, defined again when the prompt resets.
+    argv: Option<Vec<String>>,
+    natives: Vec<NativeFn>,
 }
 
 impl Mlox {
     pub fn new(args: Vec<String>) -> Self {
+        let mut env = Environment::new();
+        native::define_globals(&mut env);
         Self {
             args,
-            env: Rc::new(Mutex::new(Environment::new())),
+            env: Rc::new(Mutex::new(env)),
             error_handler: ScannerError::default(),
+            interrupt: None,
+            quiet: false,
+            script: "<prompt>".into(),
+            last_trace: vec![],
+            filesystem: true,
+            clock: Rc::new(SystemClock::new()),
+            argv: None,
+            natives: vec![],
         }
     }
 
-    pub fn interpreter(&mut self) {
-        let status = match self.args.len() - 1 {
-            0 => self.run_prompt(),
-            1 => self.run_file(),
+    /// Makes a function implemented by the host callable from scripts.
+    pub fn define_native(&mut self, native: NativeFn) {
+        self.env
+            .lock()
+            .unwrap()
+            .define(native.name, Object::native(native.clone()));
+        self.natives.push(native);
+    }
 
-            _ => 2,
-        };
+    /// The calls that were running when the last run ended with an error,
+    /// innermost first. Empty if it ended without one.
+    pub fn last_trace(&self) -> &[Frame] {
+        &self.last_trace
+    }
+
+    /// Allows or denies scripts access to the filesystem. When denied, the
+    /// file functions fail with a runtime error. Allowed by default.
+    pub fn set_filesystem(&mut self, allowed: bool) {
+        self.filesystem = allowed;
+    }
+
+    /// Replaces the clock behind `clock()`, `now()` and `sleep()`, e.g. with a
+    /// fake one in tests.
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.clock = clock;
+    }
 
-        if status > 0 {
-            println!("Usage mlox [script]")
+    /// Lets a host cancel, pause and resume the scripts run by this interpreter.
+    pub fn set_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt = Some(interrupt);
+    }
+
+    /// Runs what the command line asks for and returns the process exit code.
+    pub fn interpreter(&mut self) -> u8 {
+        let cli = match Cli::parse(&self.args) {
+            Ok(cli) => cli,
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                return EX_USAGE;
+            }
         };
+        self.quiet = cli.quiet;
+        self.set_argv(cli.script_args);
+
+        match cli.command {
+            Command::Prompt => self.run_prompt(),
+            Command::File(path) => self.run_file(&path),
+            Command::Stdin => self.run_stdin(),
+            Command::Eval(code) => {
+                self.script = "<eval>".into();
+                self.run(code).code()
+            }
+            Command::Version => {
+                println!("mlox {}", env!("CARGO_PKG_VERSION"));
+                0
+            }
+            Command::Help => {
+                println!("{USAGE}");
+                0
+            }
+        }
+    }
+
+    /// Exposes the script arguments to the program as the global list `ARGV`.
+    pub fn set_argv(&mut self, args: Vec<String>) {
+        self.argv = Some(args);
+        self.define_host_globals(&mut self.env.lock().unwrap());
+    }
+
+    /// Defines `ARGV` and the host's natives.
+    fn define_host_globals(&self, env: &mut Environment) {
+        if let Some(args) = &self.argv {
+            let argv = Object::list(args.iter().cloned().map(Object::Str).collect());
+            env.define("ARGV", argv);
+        }
+        for native in &self.natives {
+            env.define(native.name, Object::native(native.clone()));
+        }
     }
 
-    fn run_file(&mut self) -> u8 {
-        println!("[ Source file: {} ]", self.args[1]);
-        let src = std::fs::read_to_string(&self.args[1]).expect("Cannot read: {file}");
-        self.run(src);
-        return 0;
+    fn run_file(&mut self, path: &str) -> u8 {
+        if !self.quiet {
+            eprintln!("[ Source file: {} ]", path);
+        }
+        self.script = path.to_owned();
+        match std::fs::read_to_string(path) {
+            Ok(src) => self.run(src).code(),
+            Err(e) => {
+                eprintln!("Cannot read {path}: {e}");
+                EX_NOINPUT
+            }
+        }
+    }
+
+    fn run_stdin(&mut self) -> u8 {
+        self.script = "<stdin>".into();
+        let mut src = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut src) {
+            eprintln!("Cannot read stdin: {e}");
+            return EX_NOINPUT;
+        }
+        self.run(src).code()
     }
 
     fn run_prompt(&mut self) -> u8 {
-        println!("[ Prompt is running ]");
+        if !self.quiet {
+            println!("[ Prompt is running ]");
+        }
+        let mut editor: Editor<MloxHelper, DefaultHistory> =
+            Editor::new().expect("Cannot open the line editor.");
+        editor.set_helper(Some(MloxHelper::new(self.env.clone())));
+        let history = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        if let Some(path) = &history {
+            let _ = editor.load_history(path);
+        }
+
+        let mut buf = String::new();
         let status = loop {
-            let mut buf = String::new();
-            print!(">");
-            let _ = std::io::stdout().flush();
-            let n = std::io::stdin()
-                .read_line(&mut buf)
-                .expect("Cannot read stdin.");
-            match n {
-                0 => break 0, // EOF
-                1..usize::MAX => self.run(buf),
-                _ => break 1,
+            let prompt = if buf.is_empty() { "> " } else { ".. " };
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    if buf.is_empty() && line.trim_start().starts_with(':') {
+                        let _ = editor.add_history_entry(line.trim());
+                        if let Status::Exit(code) = self.run_command(line.trim()) {
+                            break code;
+                        }
+                        continue;
+                    }
+                    if !buf.is_empty() {
+                        buf.push('\n');
+                    }
+                    buf.push_str(&line);
+                    if Self::is_incomplete(&buf) {
+                        continue;
+                    }
+                    let src = std::mem::take(&mut buf);
+                    if src.trim().is_empty() {
+                        continue;
+                    }
+                    let _ = editor.add_history_entry(src.trim_end());
+                    if let Status::Exit(code) = self.run_line(src) {
+                        break code;
+                    }
+                }
+                // Ctrl-C drops the pending input but keeps the session.
+                Err(ReadlineError::Interrupted) => buf.clear(),
+                Err(ReadlineError::Eof) => break 0,
+                Err(_) => break EX_IOERR,
             }
         };
+
+        if let Some(path) = &history {
+            let _ = editor.save_history(path);
+        }
         println!();
-        return status;
+        status
+    }
+
+    /// Runs a prompt meta-command such as `:env` or `:load file.mlox`.
+    fn run_command(&mut self, line: &str) -> Status {
+        let (command, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let arg = arg.trim();
+        match command {
+            ":help" => println!("{HELP}"),
+            ":env" => {
+                for (name, value) in self.env.lock().unwrap().values() {
+                    println!("{name} = {} : {}", value.to_string(), value.type_name());
+                }
+            }
+            ":load" => match std::fs::read_to_string(arg) {
+                Ok(src) => {
+                    let prompt = std::mem::replace(&mut self.script, arg.to_owned());
+                    let status = self.run(src);
+                    self.script = prompt;
+                    return status;
+                }
+                Err(e) => eprintln!("Cannot read {arg}: {e}"),
+            },
+            ":reset" => {
+                let mut env = self.env.lock().unwrap();
+                *env = Environment::new();
+                native::define_globals(&mut env);
+                self.define_host_globals(&mut env);
+            }
+            ":ast" => {
+                let Some(tokens) = self.scan(arg) else {
+                    return Status::CompileError;
+                };
+                match Parser::new(tokens).parse_expression() {
+                    Some(expr) => expr.print_expr(),
+                    None => println!("Expect an expression."),
+                }
+            }
+            ":tokens" => {
+                for token in self.scan(arg).unwrap_or_default() {
+                    println!("{:>4} {:?} {}", token.line, token.token_type, token.lexeme);
+                }
+            }
+            ":time" => {
+                let start = Instant::now();
+                let status = self.run_line(arg.to_owned());
+                println!("[ {:?} ]", start.elapsed());
+                return status;
+            }
+            _ => println!("Unknown command '{command}', try :help."),
+        }
+        Status::Ok
+    }
+
+    /// True while the prompt input has open brackets or an unterminated string.
+    fn is_incomplete(src: &str) -> bool {
+        let mut error = ScannerError {
+            quiet: true,
+            ..Default::default()
+        };
+        let depth = Scanner::new(src)
+            .scan_tokens(&mut error)
+            .iter()
+            .fold(0, |depth, token| match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftBracket => depth + 1,
+                TokenType::RightParen | TokenType::RightBrace | TokenType::RightBracket => {
+                    depth - 1
+                }
+                _ => depth,
+            });
+        error.incomplete || depth > 0
     }
 
-    fn run(&mut self, src: String) {
-        // println!("src: {:?}", &src);
-        let mut scanner = Scanner::new(&src, &mut self.error_handler);
-        scanner.scan_tokens();
-        let mut parser = Parser::new(scanner.tokens);
-        if self.error_handler.had_err {
-            todo!("There is a syntax error!");
+    /// Runs a prompt entry, echoing its value if it is a bare expression.
+    fn run_line(&mut self, src: String) -> Status {
+        let mut error = ScannerError {
+            quiet: true,
+            ..Default::default()
+        };
+        let tokens = Scanner::new(&src).scan_tokens(&mut error);
+        if !error.had_err {
+            if let Some(expr) = Parser::new(tokens).parse_expression() {
+                return self.execute(vec![Stmt::PrintStmt(Ok(expr))]);
+            }
         }
+        self.run(src)
+    }
+
+    /// Scans the source, reporting any error, and returns its tokens if it had none.
+    fn scan(&mut self, src: &str) -> Option<Vec<Token>> {
+        self.error_handler = ScannerError::default();
+        let tokens = Scanner::new(src).scan_tokens(&mut self.error_handler);
+        (!self.error_handler.had_err).then_some(tokens)
+    }
+
+    pub fn run(&mut self, src: String) -> Status {
+        let mut parser = Parser::from_lexer(Scanner::new(&src));
         let stmts = parser.parse();
-        Evaluate::new(Rc::new(stmts), self.env.clone()).eval();
+        if parser.had_error() {
+            return Status::CompileError;
+        }
+        self.execute(stmts)
+    }
+
+    fn execute(&mut self, stmts: Vec<Stmt>) -> Status {
+        let result = Evaluate::new(Rc::new(stmts), self.env.clone())
+            .with_script(&self.script)
+            .with_interrupt(self.interrupt.clone())
+            .with_filesystem(self.filesystem)
+            .with_clock(self.clock.clone())
+            .eval();
+        self.last_trace = match &result {
+            Ok(()) => vec![],
+            Err(e) => e.stack().to_vec(),
+        };
+        match result {
+            Ok(()) => Status::Ok,
+            Err(RuntimeError::Exit(code)) => Status::Exit(code),
+            Err(RuntimeError::Interrupted) => {
+                eprintln!("[ Interrupted ]");
+                Status::Interrupted
+            }
+            Err(e) => {
+                eprintln!("{}", e.report(&self.script));
+                Status::RuntimeError
+            }
+        }
     }
 }
@@ -0,0 +1,212 @@
+use std::rc::Rc;
+
+use crate::{
+    environ::Environment,
+    error::{ErrorValue, RuntimeError},
+    eval::Evaluate,
+    io, iter, list, map, math, num, range, string, time,
+    token::{Object, Token},
+};
+
+pub type NativeFnPtr = fn(&mut Evaluate, &Token, Vec<Object>) -> Result<Object, RuntimeError>;
+
+/// A function implemented in Rust and callable from scripts.
+#[derive(Debug, Clone)]
+pub struct NativeFn {
+    pub name: &'static str,
+
+    /// Number of arguments, `None` when the function checks them itself.
+    pub arity: Option<usize>,
+    pub func: NativeFnPtr,
+}
+
+/// A namespace of values, such as `math`, whose members are read with
+/// `module.name`.
+#[derive(Debug)]
+pub struct Module {
+    pub name: &'static str,
+    pub members: Vec<(&'static str, Object)>,
+}
+
+impl Module {
+    pub fn get(&self, name: &str) -> Option<Object> {
+        self.members
+            .iter()
+            .find(|(member, _)| *member == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.members
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+}
+
+impl PartialEq for NativeFn {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+/// Defines the built-in functions in the global scope.
+pub fn define_globals(env: &mut Environment) {
+    let natives = [
+        NativeFn {
+            name: "exit",
+            arity: Some(1),
+            func: exit,
+        },
+        NativeFn {
+            name: "len",
+            arity: Some(1),
+            func: len,
+        },
+        NativeFn {
+            name: "Error",
+            arity: Some(1),
+            func: error,
+        },
+        NativeFn {
+            name: "int",
+            arity: Some(1),
+            func: int,
+        },
+        NativeFn {
+            name: "float",
+            arity: Some(1),
+            func: float,
+        },
+        NativeFn {
+            name: "str",
+            arity: Some(1),
+            func: str,
+        },
+        NativeFn {
+            name: "trace",
+            arity: Some(0),
+            func: trace,
+        },
+    ];
+    let modules = [io::FUNCTIONS, time::FUNCTIONS];
+    for native in natives
+        .into_iter()
+        .chain(modules.into_iter().flatten().cloned())
+    {
+        env.define(native.name, Object::native(native));
+    }
+    let math = math::module();
+    env.define(math.name, Object::Module(Rc::new(math)));
+}
+
+/// Methods that can be called on `object` with `object.name(...)`.
+pub fn methods(object: &Object) -> &'static [NativeFn] {
+    match object {
+        Object::List(_) => list::METHODS,
+        Object::Map(_) => map::METHODS,
+        Object::Str(_) => string::METHODS,
+        Object::Range(_) => range::METHODS,
+        Object::Iterator(_) => iter::METHODS,
+        _ => &[],
+    }
+}
+
+/// Looks up `object.name`, binding the method to its receiver.
+pub fn method(object: &Object, name: &str) -> Option<Object> {
+    let method = methods(object).iter().find(|m| m.name == name)?;
+    Some(Object::Method(
+        Box::new(object.clone()),
+        Rc::new(method.clone()),
+    ))
+}
+
+/// `len(value)`, the number of items in a list, map or range, or characters in a string.
+fn len(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    match &args[0] {
+        Object::Str(s) => Ok(Object::Int(s.chars().count() as i64)),
+        Object::List(items) => Ok(Object::Int(items.borrow().len() as i64)),
+        Object::Map(map) => Ok(Object::Int(map.borrow().len() as i64)),
+        Object::Range(range) => Ok(Object::Int(range.int_len(paren)?)),
+        other => Err(RuntimeError::new(
+            paren,
+            &format!("{} has no length.", other.type_name()),
+        )),
+    }
+}
+
+/// `Error(message)`, an error value to `throw`.
+fn error(eval: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let mut stack = eval.frames();
+    // Leave out the call to `Error` itself.
+    stack.remove(0);
+    Ok(Object::Error(Rc::new(ErrorValue {
+        message: args[0].to_string(),
+        line: paren.line,
+        stack,
+    })))
+}
+
+/// `trace()`, the calls in progress as a list of strings, innermost first.
+fn trace(eval: &mut Evaluate, _: &Token, _: Vec<Object>) -> Result<Object, RuntimeError> {
+    let mut stack = eval.frames();
+    // Leave out the call to `trace` itself.
+    stack.remove(0);
+    Ok(Object::list(
+        stack
+            .iter()
+            .map(|frame| Object::Str(frame.to_string()))
+            .collect(),
+    ))
+}
+
+/// `int(value)`, truncating a float or parsing a string.
+fn int(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let int = match &args[0] {
+        Object::Int(n) => Some(*n),
+        Object::Num(n) => num::to_int(n.trunc()),
+        #[cfg(feature = "bigint")]
+        Object::BigInt(_) => return Ok(args[0].clone()),
+        #[cfg(feature = "bigint")]
+        Object::Str(s) if s.trim().parse::<i64>().is_err() => {
+            if let Some(n) = crate::bigint::BigInt::parse(s.trim()) {
+                return Ok(num::from_big(n));
+            }
+            None
+        }
+        Object::Str(s) => s.trim().parse().ok(),
+        Object::Bool(b) => Some(*b as i64),
+        _ => None,
+    };
+    int.map(Object::Int).ok_or_else(|| {
+        RuntimeError::new(paren, &format!("Cannot convert {} to Int.", args[0].repr()))
+    })
+}
+
+/// `float(value)`, from an integer or by parsing a string.
+fn float(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let float = match &args[0] {
+        Object::Str(s) => s.trim().parse().ok(),
+        Object::Bool(b) => Some(*b as i64 as f64),
+        n => n.as_f64(),
+    };
+    float.map(Object::Num).ok_or_else(|| {
+        RuntimeError::new(paren, &format!("Cannot convert {} to Num.", args[0].repr()))
+    })
+}
+
+/// `str(value)`, the text `print` shows for the value.
+fn str(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(args[0].to_string()))
+}
+
+/// `exit(code)` stops the script, the process exits with `code`.
+fn exit(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    match args[0] {
+        Object::Int(code) if (0..=255).contains(&code) => Err(RuntimeError::Exit(code as u8)),
+        _ => Err(RuntimeError::new(
+            paren,
+            "Exit code must be an integer from 0 to 255.",
+        )),
+    }
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+
+#[cfg(feature = "bigint")]
+use crate::bigint::BigInt;
+use crate::{
+    error::RuntimeError,
+    token::{Object, Token, TokenType},
+};
+
+/// `l op r` for two numbers. Two integers give an integer, failing on
+/// overflow (or growing past `i64` with the `bigint` feature), and anything
+/// mixed with a float gives a float.
+pub fn arithmetic(op: &Token, l: &Object, r: &Object) -> Result<Object, RuntimeError> {
+    if op.token_type == TokenType::StarStar {
+        return power(op, l, r);
+    }
+    match (l, r) {
+        (Object::Int(l), Object::Int(r)) => int(op, *l, *r),
+        #[cfg(feature = "bigint")]
+        (l, r) if is_integer(l) && is_integer(r) => big(op, &to_big(l), &to_big(r)),
+        _ => {
+            let (l, r) = (l.as_f64().unwrap(), r.as_f64().unwrap());
+            Ok(Object::Num(match op.token_type {
+                TokenType::PLUS => l + r,
+                TokenType::MINUS => l - r,
+                TokenType::STAR => l * r,
+                TokenType::SLASH => l / r,
+                TokenType::PERCENT => l % r,
+                _ => unreachable!(),
+            }))
+        }
+    }
+}
+
+/// Integer arithmetic, division truncates toward zero.
+fn int(op: &Token, l: i64, r: i64) -> Result<Object, RuntimeError> {
+    let result = match op.token_type {
+        TokenType::PLUS => l.checked_add(r),
+        TokenType::MINUS => l.checked_sub(r),
+        TokenType::STAR => l.checked_mul(r),
+        TokenType::SLASH | TokenType::PERCENT if r == 0 => {
+            return Err(RuntimeError::new(op, "Division by zero."))
+        }
+        TokenType::SLASH => l.checked_div(r),
+        TokenType::PERCENT => l.checked_rem(r),
+        _ => unreachable!(),
+    };
+    #[cfg(feature = "bigint")]
+    if result.is_none() {
+        return big(op, &l.into(), &r.into());
+    }
+    result.map(Object::Int).ok_or_else(|| overflow(op))
+}
+
+#[cfg(feature = "bigint")]
+fn big(op: &Token, l: &BigInt, r: &BigInt) -> Result<Object, RuntimeError> {
+    let result = match op.token_type {
+        TokenType::PLUS => l + r,
+        TokenType::MINUS => l - r,
+        TokenType::STAR => l * r,
+        TokenType::SLASH | TokenType::PERCENT if r.is_zero() => {
+            return Err(RuntimeError::new(op, "Division by zero."))
+        }
+        TokenType::SLASH => l.div_rem(r).0,
+        TokenType::PERCENT => l.div_rem(r).1,
+        _ => unreachable!(),
+    };
+    Ok(from_big(result))
+}
+
+#[cfg(feature = "bigint")]
+fn is_integer(n: &Object) -> bool {
+    matches!(n, Object::Int(_) | Object::BigInt(_))
+}
+
+#[cfg(feature = "bigint")]
+fn to_big(n: &Object) -> BigInt {
+    match n {
+        Object::Int(n) => (*n).into(),
+        Object::BigInt(n) => (**n).clone(),
+        _ => unreachable!(),
+    }
+}
+
+/// An `Int` if the value fits in one, so each integer has one representation.
+#[cfg(feature = "bigint")]
+pub fn from_big(n: BigInt) -> Object {
+    match n.to_i64() {
+        Some(n) => Object::Int(n),
+        None => Object::BigInt(std::rc::Rc::new(n)),
+    }
+}
+
+pub fn negate(op: &Token, n: &Object) -> Result<Object, RuntimeError> {
+    match n {
+        #[cfg(feature = "bigint")]
+        Object::Int(i64::MIN) => Ok(from_big(-&BigInt::from(i64::MIN))),
+        #[cfg(feature = "bigint")]
+        Object::BigInt(n) => Ok(from_big(-&**n)),
+        Object::Int(n) => n.checked_neg().map(Object::Int).ok_or_else(|| overflow(op)),
+        Object::Num(n) => Ok(Object::Num(-n)),
+        _ => unreachable!(),
+    }
+}
+
+/// `l ** r`. An integer to a non-negative integer power is an integer, any
+/// other power a float.
+pub fn power(op: &Token, l: &Object, r: &Object) -> Result<Object, RuntimeError> {
+    match (l, r) {
+        (Object::Int(l), Object::Int(r)) if *r >= 0 => {
+            let result = match l {
+                0 | 1 => Some(*l),
+                -1 => Some(if r % 2 == 0 { 1 } else { -1 }),
+                _ => u32::try_from(*r).ok().and_then(|r| l.checked_pow(r)),
+            };
+            #[cfg(feature = "bigint")]
+            if result.is_none() {
+                return big_power(op, (*l).into(), *r);
+            }
+            result.map(Object::Int).ok_or_else(|| overflow(op))
+        }
+        #[cfg(feature = "bigint")]
+        (l, Object::Int(r)) if is_integer(l) && *r >= 0 => big_power(op, to_big(l), *r),
+        _ => Ok(Object::Num(l.as_f64().unwrap().powf(r.as_f64().unwrap()))),
+    }
+}
+
+/// Exponents past this would make numbers too large to be worth computing.
+#[cfg(feature = "bigint")]
+const MAX_EXPONENT: i64 = 1 << 20;
+
+/// Exponentiation by squaring. `base` is not 0, 1 or -1.
+#[cfg(feature = "bigint")]
+fn big_power(op: &Token, mut base: BigInt, mut exp: i64) -> Result<Object, RuntimeError> {
+    if exp > MAX_EXPONENT {
+        return Err(RuntimeError::new(op, "Exponent too large."));
+    }
+    let mut result = BigInt::from(1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = &result * &base;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = &base * &base;
+        }
+    }
+    Ok(from_big(result))
+}
+
+fn overflow(op: &Token) -> RuntimeError {
+    RuntimeError::new(op, "Integer overflow.")
+}
+
+/// Orders two numbers. Integers are never rounded, even against a float, so
+/// the order agrees with `==`.
+pub fn compare(l: &Object, r: &Object) -> Option<Ordering> {
+    match (l, r) {
+        (Object::Int(l), Object::Int(r)) => Some(l.cmp(r)),
+        (Object::Int(int), Object::Num(float)) => int_cmp_float(*int, *float),
+        (Object::Num(float), Object::Int(int)) => {
+            int_cmp_float(*int, *float).map(Ordering::reverse)
+        }
+        #[cfg(feature = "bigint")]
+        (l, r) if is_integer(l) && is_integer(r) => Some(to_big(l).cmp(&to_big(r))),
+        #[cfg(feature = "bigint")]
+        (Object::BigInt(big), Object::Num(float)) => big_cmp_float(big, *float),
+        #[cfg(feature = "bigint")]
+        (Object::Num(float), Object::BigInt(big)) => {
+            big_cmp_float(big, *float).map(Ordering::reverse)
+        }
+        _ => l.as_f64()?.partial_cmp(&r.as_f64()?),
+    }
+}
+
+/// Orders an integer against a float without rounding the integer.
+fn int_cmp_float(int: i64, float: f64) -> Option<Ordering> {
+    // Every i64 lies in [-2^63, 2^63), where the whole part of a float is exact.
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        None
+    } else if float >= LIMIT {
+        Some(Ordering::Less)
+    } else if float < -LIMIT {
+        Some(Ordering::Greater)
+    } else {
+        Some(int.cmp(&(float.trunc() as i64)).then(fract_order(float)))
+    }
+}
+
+/// Orders a big integer against a float without rounding either.
+#[cfg(feature = "bigint")]
+fn big_cmp_float(big: &BigInt, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        None
+    } else if float.is_infinite() {
+        Some(if float > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        })
+    } else {
+        let whole = BigInt::from_f64(float.trunc())?;
+        Some(big.cmp(&whole).then(fract_order(float)))
+    }
+}
+
+/// How an integer equal to the whole part of `float` orders against it.
+fn fract_order(float: f64) -> Ordering {
+    match float.fract() {
+        f if f > 0.0 => Ordering::Less,
+        f if f < 0.0 => Ordering::Greater,
+        _ => Ordering::Equal,
+    }
+}
+
+/// True if an integer and a float are the same number.
+pub fn int_eq_float(int: i64, float: f64) -> bool {
+    float.fract() == 0.0 && to_int(float) == Some(int)
+}
+
+/// The integer a float holds, if it is whole and in range.
+pub fn to_int(n: f64) -> Option<i64> {
+    // i64::MAX rounds up to 2^63 as a float, which is out of range.
+    (n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64).then_some(n as i64)
+}
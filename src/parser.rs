@@ -1,31 +1,106 @@
-use std::{io::Error, rc::Rc};
+use std::io::Error;
 
 use crate::{
-    environ::Environment,
-    eval::Evaluate,
+    error::{ParseError, ScanError},
     token::{Object, Token, TokenType},
     Expr, Stmt,
 };
 
-pub struct Parser {
+/// Tokens past the current one the grammar looks at.
+const LOOKAHEAD: usize = 2;
+
+pub struct Parser<'a> {
+    /// Where tokens come from, pulled as parsing needs them.
+    lexer: Box<dyn Iterator<Item = Result<Token, ScanError>> + 'a>,
+
+    /// Tokens pulled so far.
     tokens: Vec<Token>,
     current: usize,
+    error: ParseError,
+
+    /// The lexer reported an error. Parse errors after it are likely caused
+    /// by it, so they are not printed.
+    scan_failed: bool,
+
+    /// Number of loops around the statement being parsed.
+    loop_depth: usize,
 }
 
-impl Parser {
+impl Parser<'static> {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Parser::from_lexer(tokens.into_iter().map(Ok))
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses tokens as `lexer` yields them, e.g. from a
+    /// [`Scanner`](crate::scanner::Scanner), printing its errors.
+    pub fn from_lexer(lexer: impl Iterator<Item = Result<Token, ScanError>> + 'a) -> Self {
+        let mut parser = Self {
+            lexer: Box::new(lexer),
+            tokens: vec![],
+            current: 0,
+            error: ParseError::new(false),
+            scan_failed: false,
+            loop_depth: 0,
+        };
+        parser.fill();
+        parser
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.error.had_err || self.scan_failed
+    }
+
+    /// Pulls tokens from the lexer until the current one and those the
+    /// grammar looks ahead at are buffered, or the lexer is done.
+    fn fill(&mut self) {
+        while self.tokens.len() <= self.current + LOOKAHEAD {
+            if self
+                .tokens
+                .last()
+                .is_some_and(|t| t.token_type == TokenType::EOF)
+            {
+                return;
+            }
+            match self.lexer.next() {
+                Some(Ok(token)) => self.tokens.push(token),
+                Some(Err(e)) => {
+                    eprintln!("{e}");
+                    self.scan_failed = true;
+                }
+                None => return,
+            }
+        }
     }
 
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut stmts = vec![];
         while !self.is_at_end() {
-            stmts.push(self.declaration());
+            match self.declaration() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(e) => {
+                    if !self.scan_failed {
+                        eprintln!("{e}");
+                    }
+                    self.synchronize();
+                }
+            }
         }
         stmts
     }
 
-    fn statement(&mut self) -> Stmt {
+    /// Parses the tokens as a single expression with nothing after it,
+    /// so the prompt can echo values typed without a trailing ';'.
+    pub fn parse_expression(&mut self) -> Option<Box<Expr>> {
+        let expr = self.expression().ok()?;
+        if !self.is_at_end() {
+            return None;
+        }
+        Some(expr)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, Error> {
         if self.match_until(&[TokenType::FOR]) {
             return self.for_statement();
         }
@@ -39,8 +114,18 @@ impl Parser {
         if self.match_until(&[TokenType::WHILE]) {
             return self.while_statement();
         }
+        if self.match_until(&[TokenType::THROW]) {
+            return self.throw_statement();
+        }
+        if self.match_until(&[TokenType::TRY]) {
+            return self.try_statement();
+        }
+        if self.match_until(&[TokenType::BREAK, TokenType::CONTINUE]) {
+            return self.jump_statement();
+        }
 
-        if self.match_until(&[TokenType::LeftBrace]) {
+        if self.check(&TokenType::LeftBrace) && !self.is_map_start() {
+            self.advance();
             return self.block();
         }
         self.expr_statement()
@@ -48,33 +133,59 @@ impl Parser {
 
     fn expression(&mut self) -> Result<Box<Expr>, Error> {
         self.assignment()
-        // self.equality()
     }
 
     fn equality(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.comparison();
+        let mut expr = self.comparison()?;
         while self.match_until(&[TokenType::BangEqual, TokenType::EqualEqual]) {
             let op = self.previous().clone();
-            let xr = self.comparison();
-            expr = Ok(Box::new(Expr::Binary(expr, op, xr)));
+            let xr = self.comparison()?;
+            expr = Box::new(Expr::Binary(Ok(expr), op, Ok(xr)));
         }
-        expr
+        Ok(expr)
     }
 
     fn comparison(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.term();
+        let mut expr = self.range()?;
         while self.match_until(&[
             TokenType::GREATER,
             TokenType::GreaterEqual,
             TokenType::LESS,
             TokenType::LessEqual,
+            TokenType::IN,
         ]) {
             let op = self.previous().clone();
-            let xr = self.term();
-            expr = Ok(Box::new(Expr::Binary(expr, op, xr)));
+            let xr = self.range()?;
+            expr = Box::new(Expr::Binary(Ok(expr), op, Ok(xr)));
+        }
+
+        Ok(expr)
+    }
+
+    /// Ranges do not chain, `a..b..c` is an error.
+    fn range(&mut self) -> Result<Box<Expr>, Error> {
+        let expr = self.term()?;
+        if self.match_until(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let op = self.previous().clone();
+            let xr = self.term()?;
+            return Ok(Box::new(Expr::Binary(Ok(expr), op, Ok(xr))));
         }
+        Ok(expr)
+    }
 
-        expr
+    /// A `{` opens a map rather than a block when followed by `key :`.
+    fn is_map_start(&self) -> bool {
+        let next = |n: usize| self.tokens.get(self.current + n).map(|t| t.token_type);
+        matches!(
+            next(1),
+            Some(
+                TokenType::STRING
+                    | TokenType::NUMBER
+                    | TokenType::IDENTIFIER
+                    | TokenType::TRUE
+                    | TokenType::FALSE
+            )
+        ) && next(2) == Some(TokenType::COLON)
     }
 
     fn match_until(&mut self, token_types: &[TokenType]) -> bool {
@@ -97,6 +208,7 @@ impl Parser {
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
+            self.fill();
         }
         self.previous()
     }
@@ -114,35 +226,111 @@ impl Parser {
     }
 
     fn term(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.factor();
+        let mut expr = self.factor()?;
         while self.match_until(&[TokenType::PLUS, TokenType::MINUS]) {
             let op = self.previous().clone();
-            let xr = self.factor();
-            expr = Ok(Box::new(Expr::Binary(expr, op, xr)));
+            let xr = self.factor()?;
+            expr = Box::new(Expr::Binary(Ok(expr), op, Ok(xr)));
         }
-        expr
+        Ok(expr)
     }
 
     fn factor(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.unary();
-        while self.match_until(&[TokenType::SLASH, TokenType::STAR]) {
+        let mut expr = self.unary()?;
+        while self.match_until(&[TokenType::SLASH, TokenType::STAR, TokenType::PERCENT]) {
             let op = self.previous().clone();
-            let xr = self.unary();
-            expr = Ok(Box::new(Expr::Binary(expr, op, xr)));
+            let xr = self.unary()?;
+            expr = Box::new(Expr::Binary(Ok(expr), op, Ok(xr)));
         }
-        expr
+        Ok(expr)
     }
 
     fn unary(&mut self) -> Result<Box<Expr>, Error> {
         if self.match_until(&[TokenType::BANG, TokenType::MINUS]) {
             let op = self.previous().clone();
-            let xr = self.unary();
-            return Ok(Box::new(Expr::Unary(op, xr)));
+            let xr = self.unary()?;
+            return Ok(Box::new(Expr::Unary(op, Ok(xr))));
+        }
+        self.power()
+    }
+
+    /// `**` binds tighter than a unary operator on its left and groups to
+    /// the right, so `-2 ** 2` is -4 and `2 ** 3 ** 2` is `2 ** 9`.
+    fn power(&mut self) -> Result<Box<Expr>, Error> {
+        let expr = self.call()?;
+        if self.match_until(&[TokenType::StarStar]) {
+            let op = self.previous().clone();
+            let xr = self.unary()?;
+            return Ok(Box::new(Expr::Binary(Ok(expr), op, Ok(xr))));
+        }
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Box<Expr>, Error> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.match_until(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_until(&[TokenType::LeftBracket]) {
+                let bracket = self.previous().clone();
+                let index = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Box::new(Expr::Index(Ok(expr), bracket, Ok(index)));
+            } else if self.match_until(&[TokenType::DOT]) {
+                let name = self
+                    .consume(TokenType::IDENTIFIER, "Expect property name after '.'.")?
+                    .clone();
+                expr = Box::new(Expr::Get(Ok(expr), name));
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Box<Expr>) -> Result<Box<Expr>, Error> {
+        let mut args = vec![];
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if args.len() >= 255 {
+                    return Err(self.error("Can't have more than 255 arguments."));
+                }
+                args.push(Ok(self.expression()?));
+                if !self.match_until(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
+        let paren = self
+            .consume(TokenType::RightParen, "Expect ')' after arguments.")?
+            .clone();
+        Ok(Box::new(Expr::Call(Ok(callee), paren, args)))
+    }
+
+    /// `"a ${x} b"` arrives as INTERPOLATION("a "), the tokens of `x`, and
+    /// STRING(" b"), with one more INTERPOLATION for each further `${`.
+    fn interpolation(&mut self) -> Result<Box<Expr>, Error> {
+        let mut parts = vec![];
+        loop {
+            let text = self.previous().literal.clone();
+            parts.push(Ok(Box::new(Expr::Literal(text))));
+            parts.push(Ok(self.expression()?));
+            if self.match_until(&[TokenType::INTERPOLATION]) {
+                continue;
+            }
+            let end = self.consume(
+                TokenType::STRING,
+                "Expect '}' after interpolated expression.",
+            )?;
+            parts.push(Ok(Box::new(Expr::Literal(end.literal.clone()))));
+            return Ok(Box::new(Expr::Interpolate(parts)));
         }
-        self.primary()
     }
 
     fn primary(&mut self) -> Result<Box<Expr>, Error> {
+        if self.match_until(&[TokenType::INTERPOLATION]) {
+            return self.interpolation();
+        }
         if self.match_until(&[TokenType::FALSE]) {
             return Ok(Box::new(Expr::Literal(Object::Bool(false))));
         }
@@ -159,9 +347,9 @@ impl Parser {
         }
 
         if self.match_until(&[TokenType::LeftParen]) {
-            let expr = self.expression();
-            self.consume(TokenType::RightParen, "Expect ')' after expression.");
-            return Ok(Box::new(Expr::Grouping(expr)));
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+            return Ok(Box::new(Expr::Grouping(Ok(expr))));
         }
 
         if self.match_until(&[TokenType::IDENTIFIER]) {
@@ -169,183 +357,290 @@ impl Parser {
             return Ok(Box::new(Expr::Variable(token)));
         }
 
-        Err(Error::other(format!(
-            "{:?} {}",
-            self.peek(),
-            "Expect expression.",
-        )))
+        if self.match_until(&[TokenType::LeftBracket]) {
+            let mut items = vec![];
+            while !self.check(&TokenType::RightBracket) {
+                items.push(Ok(self.expression()?));
+                if !self.match_until(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBracket, "Expect ']' after list items.")?;
+            return Ok(Box::new(Expr::List(items)));
+        }
+
+        if self.match_until(&[TokenType::LeftBrace]) {
+            let brace = self.previous().clone();
+            let mut entries = vec![];
+            while !self.check(&TokenType::RightBrace) {
+                let key = self.expression()?;
+                self.consume(TokenType::COLON, "Expect ':' after map key.")?;
+                let value = self.expression()?;
+                entries.push((Ok(key), Ok(value)));
+                if !self.match_until(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+            return Ok(Box::new(Expr::Map(brace, entries)));
+        }
+
+        Err(self.error("Expect expression."))
     }
 
-    fn consume(&mut self, token_type: TokenType, msg: &str) -> &Token {
+    fn consume(&mut self, token_type: TokenType, msg: &str) -> Result<&Token, Error> {
         if self.check(&token_type) {
-            return self.advance();
+            return Ok(self.advance());
         };
-        panic!("{:?} {}", self.peek(), msg);
+        Err(self.error(msg))
+    }
+
+    /// Reports an error at the current token.
+    fn error(&mut self, msg: &str) -> Error {
+        let token = self.peek().clone();
+        self.error.send(&token, msg)
     }
 
-    fn synchronize(&mut self) -> Stmt {
+    /// Skips tokens until the start of the next statement after an error.
+    fn synchronize(&mut self) {
         self.advance();
         while !self.is_at_end() {
             if self.previous().token_type == TokenType::SEMICOLON {
-                break;
+                return;
             }
             match self.peek().token_type {
-                TokenType::CLASS => {}
-                TokenType::FUN => {}
-                TokenType::VAR => {}
-                TokenType::FOR => {}
-                TokenType::IF => {}
-                TokenType::WHILE => {}
-                TokenType::PRINT => {}
-                TokenType::RETURN => break,
+                TokenType::CLASS
+                | TokenType::FUN
+                | TokenType::VAR
+                | TokenType::FOR
+                | TokenType::IF
+                | TokenType::WHILE
+                | TokenType::PRINT
+                | TokenType::RETURN => return,
                 _ => {}
             }
+            self.advance();
         }
-        self.advance();
-        self.print_statement() // remove me
     }
 
-    fn print_statement(&mut self) -> Stmt {
-        let expr = self.expression();
-        self.consume(TokenType::SEMICOLON, "Expect ';' after value.");
-        Stmt::PrintStmt(expr)
+    fn print_statement(&mut self) -> Result<Stmt, Error> {
+        let expr = self.expression()?;
+        self.consume(TokenType::SEMICOLON, "Expect ';' after value.")?;
+        Ok(Stmt::PrintStmt(Ok(expr)))
     }
 
-    fn expr_statement(&mut self) -> Stmt {
-        let expr = self.expression();
-        self.consume(TokenType::SEMICOLON, "Expect ';' after value.");
-        Stmt::ExprStmt(expr)
+    fn expr_statement(&mut self) -> Result<Stmt, Error> {
+        let expr = self.expression()?;
+        self.consume(TokenType::SEMICOLON, "Expect ';' after value.")?;
+        Ok(Stmt::ExprStmt(Ok(expr)))
     }
 
-    fn declaration(&mut self) -> Stmt {
+    fn declaration(&mut self) -> Result<Stmt, Error> {
         if self.match_until(&[TokenType::VAR]) {
-            return self.var_declaration();
+            self.var_declaration()
         } else {
-            return self.statement();
+            self.statement()
         }
-        // self.synchronize()
     }
 
-    fn var_declaration(&mut self) -> Stmt {
+    fn var_declaration(&mut self) -> Result<Stmt, Error> {
         let name = self
-            .consume(TokenType::IDENTIFIER, "Expect varibale name.")
+            .consume(TokenType::IDENTIFIER, "Expect varibale name.")?
             .clone();
 
         let mut init = None;
         if self.match_until(&[TokenType::EQUAL]) {
-            init = Some(self.expression());
+            init = Some(Ok(self.expression()?));
         }
         self.consume(
             TokenType::SEMICOLON,
             "Expect ';' after variable declaration.",
-        );
-        return Stmt::VarStmt(name, init);
+        )?;
+        Ok(Stmt::VarStmt(name, init))
     }
 
     fn assignment(&mut self) -> Result<Box<Expr>, Error> {
-        let expr = self.logic_or();
+        let expr = self.logic_or()?;
         if self.match_until(&[TokenType::EQUAL]) {
             let eq = self.previous().clone();
-            let value = self.assignment();
-            match *expr.unwrap() {
-                Expr::Variable(t) => return Ok(Box::new(Expr::Assign(t, value))),
-                _ => todo!(),
+            let value = self.assignment()?;
+            return match *expr {
+                Expr::Variable(t) => Ok(Box::new(Expr::Assign(t, Ok(value)))),
+                Expr::Index(object, bracket, index) => {
+                    Ok(Box::new(Expr::SetIndex(object, bracket, index, Ok(value))))
+                }
+                _ => Err(self.error.send(&eq, "Invalid assignment target.")),
             };
         }
-        expr
+        Ok(expr)
     }
 
-    fn block(&mut self) -> Stmt {
+    fn block(&mut self) -> Result<Stmt, Error> {
         let mut stmts = vec![];
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
-            stmts.push(Box::new(self.declaration()));
+            stmts.push(Box::new(self.declaration()?));
         }
-        self.consume(TokenType::RightBrace, "Expect '}' after block.");
-        return Stmt::BlockStmt(stmts);
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(Stmt::BlockStmt(stmts))
     }
 
-    fn if_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.");
-        let condition = self.expression();
-        self.consume(TokenType::RightParen, "Expect ')' after 'if condition' .");
-        let then_branch = self.statement();
+    fn if_statement(&mut self) -> Result<Stmt, Error> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after 'if condition' .")?;
+        let then_branch = self.statement()?;
         let mut else_branch = None;
         if self.match_until(&[TokenType::ELSE]) {
-            else_branch = Some(Box::new(self.statement()));
+            else_branch = Some(Box::new(self.statement()?));
         }
-        Stmt::IfStmt(condition, Box::new(then_branch), else_branch)
+        Ok(Stmt::IfStmt(
+            Ok(condition),
+            Box::new(then_branch),
+            else_branch,
+        ))
     }
 
     fn logic_or(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.logic_and();
+        let mut expr = self.logic_and()?;
         while self.match_until(&[TokenType::OR]) {
             let op = self.previous().clone();
-            let right = self.logic_and();
-            expr = Ok(Box::new(Expr::Logical(expr, op, right)));
+            let right = self.logic_and()?;
+            expr = Box::new(Expr::Logical(Ok(expr), op, Ok(right)));
         }
-        expr
+        Ok(expr)
     }
 
     fn logic_and(&mut self) -> Result<Box<Expr>, Error> {
-        let mut expr = self.equality();
+        let mut expr = self.equality()?;
         while self.match_until(&[TokenType::AND]) {
             let op = self.previous().clone();
-            let right = self.equality();
-            expr = Ok(Box::new(Expr::Logical(expr, op, right)));
+            let right = self.equality()?;
+            expr = Box::new(Expr::Logical(Ok(expr), op, Ok(right)));
         }
-        expr
+        Ok(expr)
     }
 
-    fn while_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.");
-        let cond = self.expression();
-        self.consume(TokenType::RightParen, "Expect ')' after 'while condition'.");
+    fn while_statement(&mut self) -> Result<Stmt, Error> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let cond = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after 'while condition'.")?;
+        let body = self.loop_body()?;
+        Ok(Stmt::WhileStmt(Ok(cond), Box::new(body), None))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous().clone();
+        let value = self.expression()?;
+        self.consume(TokenType::SEMICOLON, "Expect ';' after thrown value.")?;
+        Ok(Stmt::ThrowStmt(keyword, Ok(value)))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, Error> {
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+        let mut catch = None;
+        if self.match_until(&[TokenType::CATCH]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'.")?;
+            let name = self
+                .consume(TokenType::IDENTIFIER, "Expect varibale name.")?
+                .clone();
+            self.consume(TokenType::RightParen, "Expect ')' after catch variable.")?;
+            self.consume(TokenType::LeftBrace, "Expect '{' after catch clause.")?;
+            catch = Some((name, Box::new(self.block()?)));
+        }
+        let mut finally = None;
+        if self.match_until(&[TokenType::FINALLY]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            finally = Some(Box::new(self.block()?));
+        }
+        if catch.is_none() && finally.is_none() {
+            return Err(self.error("Expect 'catch' or 'finally' after try block."));
+        }
+        Ok(Stmt::TryStmt(Box::new(body), catch, finally))
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, Error> {
+        self.loop_depth += 1;
         let body = self.statement();
-        Stmt::WhileStmt(cond, Box::new(body))
+        self.loop_depth -= 1;
+        body
+    }
+
+    /// `break;` or `continue;`, only allowed inside a loop.
+    fn jump_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous().clone();
+        if self.loop_depth == 0 {
+            return Err(self.error.send(
+                &keyword,
+                &format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+            ));
+        }
+        self.consume(
+            TokenType::SEMICOLON,
+            &format!("Expect ';' after '{}'.", keyword.lexeme),
+        )?;
+        Ok(match keyword.token_type {
+            TokenType::BREAK => Stmt::BreakStmt(keyword),
+            _ => Stmt::ContinueStmt(keyword),
+        })
+    }
+
+    fn for_in_statement(&mut self) -> Result<Stmt, Error> {
+        let name = self
+            .consume(TokenType::IDENTIFIER, "Expect varibale name.")?
+            .clone();
+        self.consume(TokenType::IN, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after loop iterable.")?;
+        let body = self.loop_body()?;
+        Ok(Stmt::ForInStmt(name, Ok(iterable), Box::new(body)))
     }
 
-    fn for_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.");
+    fn for_statement(&mut self) -> Result<Stmt, Error> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+        if self.tokens.get(self.current + 2).map(|t| t.token_type) == Some(TokenType::IN)
+            && self.match_until(&[TokenType::VAR])
+        {
+            return self.for_in_statement();
+        }
 
         let mut _init = None;
         if self.match_until(&[TokenType::SEMICOLON]) {
             _init = None
         } else if self.match_until(&[TokenType::VAR]) {
-            _init = Some(self.var_declaration());
+            _init = Some(self.var_declaration()?);
         } else {
-            _init = Some(self.expr_statement());
+            _init = Some(self.expr_statement()?);
         }
 
         let mut cond = None;
         if !self.check(&TokenType::SEMICOLON) {
-            cond = Some(self.expression());
+            cond = Some(self.expression()?);
         }
-        self.consume(TokenType::SEMICOLON, "Expect ';' after loop condition.");
+        self.consume(TokenType::SEMICOLON, "Expect ';' after loop condition.")?;
 
         let mut increment = None;
         if !self.check(&TokenType::RightParen) {
-            increment = Some(self.expression());
+            increment = Some(self.expression()?);
         }
-        self.consume(TokenType::RightParen, "Expect ';' after loop condition.");
+        self.consume(TokenType::RightParen, "Expect ';' after loop condition.")?;
 
-        let mut body = Some(self.statement());
-        if increment.is_some() {
-            body = Some(Stmt::BlockStmt(vec![
-                Box::new(body.unwrap()),
-                Box::new(Stmt::ExprStmt(increment.unwrap())),
-            ]));
-        };
+        let mut body = Some(self.loop_body()?);
         if cond.is_none() {
-            cond = Some(Ok(Box::new(Expr::Literal(Object::Bool(true)))));
+            cond = Some(Box::new(Expr::Literal(Object::Bool(true))));
         }
-        body = Some(Stmt::WhileStmt(cond.unwrap(), Box::new(body.unwrap())));
+        body = Some(Stmt::WhileStmt(
+            Ok(cond.unwrap()),
+            Box::new(body.unwrap()),
+            increment.map(Ok),
+        ));
 
-        if _init.is_some() {
+        if let Some(init) = _init {
             body = Some(Stmt::BlockStmt(vec![
-                Box::new(_init.unwrap()),
+                Box::new(init),
                 Box::new(body.unwrap()),
             ]));
         }
-        body.unwrap()
+        Ok(body.unwrap())
     }
 }
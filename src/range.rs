@@ -0,0 +1,170 @@
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    iter,
+    native::NativeFn,
+    num,
+    token::{Object, Token},
+};
+
+/// A lazy sequence of whole numbers, made by `start..end` or `start..=end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+    pub step: i64,
+}
+
+/// Methods of range values.
+pub const METHODS: &[NativeFn] = &[
+    NativeFn {
+        name: "len",
+        arity: Some(0),
+        func: len,
+    },
+    NativeFn {
+        name: "step",
+        arity: Some(1),
+        func: step,
+    },
+    NativeFn {
+        name: "contains",
+        arity: Some(1),
+        func: contains,
+    },
+    NativeFn {
+        name: "to_list",
+        arity: Some(0),
+        func: to_list,
+    },
+    iter::ITER,
+];
+
+impl Range {
+    /// Builds `start..end`, or `start..=end` when `inclusive`.
+    pub fn new(
+        token: &Token,
+        start: &Object,
+        end: &Object,
+        inclusive: bool,
+    ) -> Result<Self, RuntimeError> {
+        Ok(Self {
+            start: int(token, start, "Range bounds must be integers.")?,
+            end: int(token, end, "Range bounds must be integers.")?,
+            inclusive,
+            step: 1,
+        })
+    }
+
+    /// How many numbers the range holds, which may be more than an Int holds.
+    pub fn len(&self) -> i128 {
+        // Move an inclusive end one step past it so both kinds stop before `end`.
+        let end = match (self.inclusive, self.step > 0) {
+            (false, _) => self.end as i128,
+            (true, true) => self.end as i128 + 1,
+            (true, false) => self.end as i128 - 1,
+        };
+        let (span, step) = (end - self.start as i128, self.step as i128);
+        match span.signum() == step.signum() {
+            true => (span + step - step.signum()) / step,
+            false => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `len()` as an Int, failing for a range too long for one.
+    pub fn int_len(&self, token: &Token) -> Result<i64, RuntimeError> {
+        i64::try_from(self.len())
+            .map_err(|_| RuntimeError::new(token, "Range is too long to have a length."))
+    }
+
+    /// The `n`th number of the range, `n` must be below `len()`.
+    pub fn get(&self, n: i128) -> i64 {
+        (self.start as i128 + n * self.step as i128) as i64
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        let offset = n as i128 - self.start as i128;
+        let step = self.step as i128;
+        offset % step == 0 && offset / step >= 0 && offset / step < self.len()
+    }
+
+    /// Positions the range selects in a sequence of `len` items, counting
+    /// negative bounds from the end as indexes do.
+    pub fn positions(&self, token: &Token, len: usize) -> Result<Vec<usize>, RuntimeError> {
+        let resolve = |n: i64| if n < 0 { n + len as i64 } else { n };
+        let range = Self {
+            start: resolve(self.start),
+            end: resolve(self.end),
+            ..*self
+        };
+        (0..range.len())
+            .map(|n| match range.get(n) {
+                at if (0..len as i64).contains(&at) => Ok(at as usize),
+                _ => Err(RuntimeError::new(
+                    token,
+                    &format!("Range {} out of bounds for length {len}.", self.to_string()),
+                )),
+            })
+            .collect()
+    }
+
+    pub fn to_string(&self) -> String {
+        let op = if self.inclusive { "..=" } else { ".." };
+        match self.step {
+            1 => format!("{}{op}{}", self.start, self.end),
+            step => format!("({}{op}{}).step({step})", self.start, self.end),
+        }
+    }
+}
+
+fn int(token: &Token, n: &Object, message: &str) -> Result<i64, RuntimeError> {
+    match n {
+        Object::Int(n) => Ok(*n),
+        _ => Err(RuntimeError::new(token, message)),
+    }
+}
+
+fn range(args: &[Object]) -> Range {
+    match &args[0] {
+        Object::Range(range) => *range,
+        _ => unreachable!("range method called on {}", args[0].type_name()),
+    }
+}
+
+fn len(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(range(&args).int_len(paren)?))
+}
+
+/// `r.step(n)`, the same range taking every `n`th number.
+fn step(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let step = int(paren, &args[1], "Range step must be an integer.")?;
+    if step == 0 {
+        return Err(RuntimeError::new(paren, "Range step cannot be zero."));
+    }
+    Ok(Object::Range(Range {
+        step,
+        ..range(&args)
+    }))
+}
+
+fn contains(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(match args[1] {
+        Object::Int(n) => range(&args).contains(n),
+        Object::Num(n) => num::to_int(n).is_some_and(|n| range(&args).contains(n)),
+        _ => false,
+    }))
+}
+
+fn to_list(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let range = range(&args);
+    Ok(Object::list(
+        (0..range.int_len(paren)? as i128)
+            .map(|n| Object::Int(range.get(n)))
+            .collect(),
+    ))
+}
@@ -0,0 +1,220 @@
+use std::ops::Range as Span;
+
+use crate::{error::ScanError, scanner::Scanner, token::Token};
+
+/// A source and its tokens, kept up to date as the source is edited by
+/// scanning again only around each edit. Meant for editors and other tools
+/// that re-tokenize a file on every keystroke.
+pub struct Lexed {
+    source: String,
+    tokens: Vec<Token>,
+    errors: Vec<ScanError>,
+
+    /// Whether scanning can restart at the start of each token, i.e. it is
+    /// not inside a string's `${...}`.
+    restartable: Vec<bool>,
+}
+
+impl Lexed {
+    pub fn new(source: String) -> Self {
+        let mut lexed = Self {
+            source,
+            tokens: vec![],
+            errors: vec![],
+            restartable: vec![],
+        };
+        let scanner = Scanner::new(&lexed.source);
+        let (tokens, restartable, errors) = scan_until(scanner, |_, _| false);
+        lexed.tokens = tokens;
+        lexed.restartable = restartable;
+        lexed.errors = errors;
+        lexed
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The tokens, ending with EOF.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
+    /// Replaces the bytes of the source in `range` with `text`, like
+    /// [`String::replace_range`], and updates the tokens. Scanning restarts
+    /// at the last token that ends before the edit and stops once the new
+    /// tokens line up with the old ones again.
+    ///
+    /// Returns the indexes of the tokens that were scanned again; the ones
+    /// before are unchanged and the ones after only moved.
+    pub fn edit(&mut self, range: Span<usize>, text: &str) -> Span<usize> {
+        let removed_lines = self.source[range.clone()].matches('\n').count();
+        self.source.replace_range(range.clone(), text);
+        let delta = text.len() as isize - range.len() as isize;
+        let line_delta = text.matches('\n').count() as isize - removed_lines as isize;
+
+        let first = (0..self.tokens.len())
+            .rev()
+            .find(|&i| self.restartable[i] && self.tokens[i].span.end < range.start);
+        let scanner = match first {
+            Some(i) => {
+                let token = &self.tokens[i];
+                let line = token.line - token.lexeme.matches('\n').count();
+                Scanner::starting_at(&self.source, token.span.start, line)
+            }
+            None => Scanner::new(&self.source),
+        };
+        let first = first.unwrap_or(0);
+        let restart = scanner.offset();
+
+        // Old tokens after the edit, which the new ones may line up with.
+        let old = &self.tokens;
+        let old_restartable = &self.restartable;
+        let mut next_old = old.partition_point(|t| t.span.start < range.end);
+        let mut resync = None;
+        let (tokens, restartable, errors) = scan_until(scanner, |token, at_top_level| {
+            let start = token.span.start as isize - delta;
+            while next_old < old.len() && (old[next_old].span.start as isize) < start {
+                next_old += 1;
+            }
+            let Some(candidate) = old.get(next_old) else {
+                return false;
+            };
+            let lines_up = at_top_level
+                && old_restartable[next_old]
+                && candidate.span.start as isize == start
+                && candidate.token_type == token.token_type
+                && candidate.lexeme == token.lexeme;
+            if lines_up {
+                resync = Some(next_old);
+            }
+            lines_up
+        });
+
+        let end = first + tokens.len();
+        let resync = resync.unwrap_or(self.tokens.len());
+        let old_end = self.tokens.get(resync).map_or(usize::MAX, |t| t.span.start);
+        let shift_span = |span: &Span<usize>| {
+            (span.start as isize + delta) as usize..(span.end as isize + delta) as usize
+        };
+        let shift_line = |line: usize| (line as isize + line_delta) as usize;
+
+        for token in &mut self.tokens[resync..] {
+            token.span = shift_span(&token.span);
+            token.line = shift_line(token.line);
+        }
+        self.tokens.splice(first..resync, tokens);
+        self.restartable.splice(first..resync, restartable);
+
+        let tail: Vec<_> = self
+            .errors
+            .iter()
+            .filter(|e| e.at >= old_end)
+            .map(|e| ScanError {
+                line: shift_line(e.line),
+                at: (e.at as isize + delta) as usize,
+                ..e.clone()
+            })
+            .collect();
+        self.errors.retain(|e| e.at < restart);
+        self.errors.extend(errors);
+        self.errors.extend(tail);
+
+        first..end
+    }
+}
+
+/// Pulls tokens from `scanner` until `stop` accepts one, which is left out,
+/// or the source ends. `stop` is also told whether scanning could restart at
+/// the token. Returns the tokens, whether each is restartable, and the errors.
+fn scan_until(
+    mut scanner: Scanner,
+    mut stop: impl FnMut(&Token, bool) -> bool,
+) -> (Vec<Token>, Vec<bool>, Vec<ScanError>) {
+    let mut tokens = vec![];
+    let mut restartable = vec![];
+    let mut errors = vec![];
+    loop {
+        let at_top_level = scanner.at_top_level();
+        match scanner.next() {
+            Some(Ok(token)) => {
+                if stop(&token, at_top_level) {
+                    break;
+                }
+                tokens.push(token);
+                restartable.push(at_top_level);
+            }
+            Some(Err(e)) => errors.push(e),
+            None => break,
+        }
+    }
+    (tokens, restartable, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edits `source` and checks the result against scanning it from scratch.
+    fn check(source: &str, range: Span<usize>, text: &str) {
+        let mut lexed = Lexed::new(source.to_owned());
+        lexed.edit(range.clone(), text);
+        let mut edited = source.to_owned();
+        edited.replace_range(range, text);
+        let fresh = Lexed::new(edited.clone());
+
+        assert_eq!(lexed.source(), edited);
+        let summary = |lexed: &Lexed| {
+            lexed
+                .tokens()
+                .iter()
+                .map(|t| {
+                    (
+                        t.token_type,
+                        t.lexeme.clone(),
+                        t.literal.clone(),
+                        t.line,
+                        t.span.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&lexed), summary(&fresh), "{edited:?}");
+        assert_eq!(lexed.errors(), fresh.errors(), "{edited:?}");
+    }
+
+    const SOURCE: &str = "var a = 1;\nprint \"x ${a + 1} y\";\n/* c */ var b = a;\n";
+
+    #[test]
+    fn edits_match_a_full_rescan() {
+        check(SOURCE, 4..5, "abc");
+        check(SOURCE, 0..0, "\n\n");
+        check(SOURCE, 10..11, "");
+        check(SOURCE, 20..21, "b * 2");
+        check(SOURCE, 22..23, "b * 2");
+        check(SOURCE, 8..9, "\"open");
+        check(SOURCE, 35..35, "/*");
+        check(SOURCE, SOURCE.len()..SOURCE.len(), "print a;");
+        check(SOURCE, 0..SOURCE.len(), "");
+    }
+
+    #[test]
+    fn errors_after_the_edit_move_with_it() {
+        check("var a = @;\nvar b = #;\n", 0..0, "\n");
+        check("var a = @;\nvar b = #;\n", 8..9, "1");
+        check("\"unterminated", 0..0, "x ");
+    }
+
+    #[test]
+    fn edit_reports_the_rescanned_tokens() {
+        let mut lexed = Lexed::new("a b c d".to_owned());
+        let rescanned = lexed.edit(4..5, "x");
+        assert!(rescanned.start <= 2 && rescanned.end >= 3);
+        assert!(rescanned.end < lexed.tokens().len());
+        assert_eq!(lexed.tokens()[2].lexeme, "x");
+    }
+}
@@ -1,25 +1,34 @@
-use crate::error::ScannerError;
+use crate::error::{ScanError, ScannerError};
 use crate::token::{Object, Token, TokenType};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::str::Chars;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
 thread_local! {
     static KEYWORDS: RefCell< HashMap<&'static str, TokenType> > =
         HashMap::from([
                       ("and", TokenType::AND),
+                      ("break", TokenType::BREAK),
+                      ("catch", TokenType::CATCH),
                       ("class", TokenType::CLASS),
+                      ("continue", TokenType::CONTINUE),
                       ("else", TokenType::ELSE),
                       ("false", TokenType::FALSE),
+                      ("finally", TokenType::FINALLY),
                       ("for", TokenType::FOR),
                       ("fun", TokenType::FUN),
                       ("if", TokenType::IF),
+                      ("in", TokenType::IN),
                       ("null", TokenType::Null),
                       ("or", TokenType::OR),
                       ("print", TokenType::PRINT),
                       ("ret", TokenType::RETURN),
                       ("super", TokenType::SUPER),
                       ("this", TokenType::THIS),
+                      ("throw", TokenType::THROW),
                       ("true", TokenType::TRUE),
+                      ("try", TokenType::TRY),
                       ("var", TokenType::VAR),
                       ("while", TokenType::WHILE),
 
@@ -27,44 +36,93 @@ thread_local! {
 
 }
 
+/// Every reserved word, for the prompt's completion.
+pub fn keywords() -> Vec<&'static str> {
+    KEYWORDS.with(|kw| kw.borrow().keys().copied().collect())
+}
+
+/// Turns source into tokens one at a time, as the parser asks for them.
 pub struct Scanner<'a> {
     /// Source code.
-    source: &'a String,
+    source: &'a str,
 
-    /// List of tokens.
-    pub tokens: Vec<Token>,
-
-    ///  Points at the first character in the lexeme being scanned.
+    /// Byte offset of the first character in the lexeme being scanned.
     start: usize,
 
-    /// Points at the character currently being considered.
+    /// Byte offset of the character currently being considered.
     current: usize,
 
     ///  Field tracks what source line current is on.
     line: usize,
 
-    /// Error handle.
-    error: &'a mut ScannerError,
+    /// Tokens and errors scanned but not yet handed out. Scanning one lexeme
+    /// yields at most a couple of them.
+    pending: VecDeque<Result<Token, ScanError>>,
+
+    /// The EOF token has been handed out.
+    done: bool,
+
+    /// Strings whose `${` we are inside: their quote and the depth of `{`
+    /// opened since, so the right `}` resumes the string.
+    interpolations: Vec<(char, usize)>,
 }
 
 impl<'a> Scanner<'a> {
-    pub fn new(source: &'a String, error: &'a mut ScannerError) -> Self {
+    pub fn new(source: &'a str) -> Self {
+        Self::starting_at(source, 0, 1)
+    }
+
+    /// Scans `source` from byte `offset`, which is on `line`. The offset must
+    /// be the start of a token outside any string.
+    pub fn starting_at(source: &'a str, offset: usize, line: usize) -> Self {
         Self {
             source,
-            tokens: vec![],
-            start: 0,
-            current: 0,
-            line: 1,
-            error,
+            start: offset,
+            current: offset,
+            line,
+            pending: VecDeque::new(),
+            done: false,
+            interpolations: vec![],
+        }
+    }
+
+    /// Scans the whole source, passing errors to `error`.
+    pub fn scan_tokens(self, error: &mut ScannerError) -> Vec<Token> {
+        let mut tokens = vec![];
+        for token in self {
+            match token {
+                Ok(token) => tokens.push(token),
+                Err(e) => error.add(&e),
+            }
         }
+        tokens
+    }
+
+    /// Byte offset the next token will be scanned from.
+    pub fn offset(&self) -> usize {
+        self.current
+    }
+
+    /// True if the scanner is not inside a string's `${...}`.
+    pub fn at_top_level(&self) -> bool {
+        self.interpolations.is_empty()
+    }
+
+    fn error(&mut self, line: usize, message: &str) {
+        self.pending.push_back(Err(ScanError {
+            line,
+            at: self.start,
+            message: message.to_string(),
+            incomplete: false,
+        }));
     }
 
-    pub fn scan_tokens(&mut self) {
-        while !self.is_at_end() {
-            self.start = self.current;
-            self.scan_token();
+    /// Reports the source ending in the middle of a token.
+    fn incomplete(&mut self, line: usize, message: &str) {
+        self.error(line, message);
+        if let Some(Err(e)) = self.pending.back_mut() {
+            e.incomplete = true;
         }
-        self.add_none_token(TokenType::EOF);
     }
 
     fn add_none_token(&mut self, token_type: TokenType) {
@@ -73,8 +131,13 @@ impl<'a> Scanner<'a> {
 
     fn add_token(&mut self, token_type: TokenType, literal: Object) {
         let text = &self.source[self.start..self.current];
-        self.tokens
-            .push(Token::new(token_type, text.to_string(), literal, self.line));
+        self.pending.push_back(Ok(Token::new(
+            token_type,
+            text.to_string(),
+            literal,
+            self.line,
+            self.start..self.current,
+        )));
     }
 
     /// True if we consumed all the characters.
@@ -88,14 +151,48 @@ impl<'a> Scanner<'a> {
         match ch {
             '(' => self.add_none_token(TokenType::LeftParen),
             ')' => self.add_none_token(TokenType::RightParen),
-            '{' => self.add_none_token(TokenType::LeftBrace),
-            '}' => self.add_none_token(TokenType::RightBrace),
+            '{' => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_none_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some((quote, 0)) => {
+                    let quote = *quote;
+                    self.interpolations.pop();
+                    self.string_check(quote)
+                }
+                Some((_, depth)) => {
+                    *depth -= 1;
+                    self.add_none_token(TokenType::RightBrace)
+                }
+                None => self.add_none_token(TokenType::RightBrace),
+            },
+            '[' => self.add_none_token(TokenType::LeftBracket),
+            ']' => self.add_none_token(TokenType::RightBracket),
+            ':' => self.add_none_token(TokenType::COLON),
             ',' => self.add_none_token(TokenType::COMMA),
-            '.' => self.add_none_token(TokenType::DOT),
+            '.' => {
+                if !self.match_next('.') {
+                    self.add_none_token(TokenType::DOT)
+                } else if self.match_next('=') {
+                    self.add_none_token(TokenType::DotDotEqual)
+                } else {
+                    self.add_none_token(TokenType::DotDot)
+                }
+            }
             '-' => self.add_none_token(TokenType::MINUS),
             '+' => self.add_none_token(TokenType::PLUS),
             ';' => self.add_none_token(TokenType::SEMICOLON),
-            '*' => self.add_none_token(TokenType::STAR),
+            '*' => {
+                if self.match_next('*') {
+                    self.add_none_token(TokenType::StarStar)
+                } else {
+                    self.add_none_token(TokenType::STAR)
+                }
+            }
+            '%' => self.add_none_token(TokenType::PERCENT),
             '!' => {
                 if self.match_next('=') {
                     self.add_none_token(TokenType::BangEqual)
@@ -132,6 +229,8 @@ impl<'a> Scanner<'a> {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.match_next('*') {
+                    self.block_comment();
                 } else {
                     self.add_none_token(TokenType::SLASH)
                 }
@@ -139,110 +238,405 @@ impl<'a> Scanner<'a> {
             ' ' | '\r' | '\t' => {}
             '\n' => self.line += 1,
 
+            '"' if self.peek() == '"' && self.peek_next() == '"' => {
+                self.advance();
+                self.advance();
+                self.text_block();
+            }
             sc @ ('"' | '\'') => self.string_check(sc),
+            'r' if matches!(self.peek(), '"' | '\'') => {
+                let quote = self.advance();
+                self.raw_string(quote)
+            }
             '0'..='9' => self.number_check(),
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier_check(),
+            c if c == '_' || is_xid_start(c) => self.identifier_check(),
 
-            _ => {
-                self.error.send(self.line, "Unexpected character.");
+            c => {
+                let message = format!("Unexpected character {c:?}.");
+                self.error(self.line, &message);
             }
         }
     }
 
     /// Consumes the next character in the source file and returns it.
     fn advance(&mut self) -> char {
-        let ch = self.source.chars().nth(self.current).expect(&format!(
-            "scanner.advance: canot read char at {}",
-            self.current
-        ));
-        self.current += 1;
-        ch
+        match self.rest().next() {
+            Some(ch) => {
+                self.current += ch.len_utf8();
+                ch
+            }
+            None => '\0',
+        }
     }
 
     fn match_next(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self
-            .source
-            .chars()
-            .nth(self.current)
-            .expect("scanner.match_next: cannot reac char at {}")
-            != expected
-        {
+        if self.rest().next() != Some(expected) {
             return false;
         }
-        self.current += 1;
+        self.current += expected.len_utf8();
         true
     }
 
+    /// The source left to scan. Reading a few characters from it costs the
+    /// same wherever we are in the file.
+    fn rest(&self) -> Chars<'a> {
+        self.source[self.current..].chars()
+    }
+
     fn peek(&self) -> char {
+        self.rest().next().unwrap_or('\0')
+    }
+
+    /// Scans a string, or its rest after a `${...}`, up to the closing quote
+    /// or the next `${`.
+    fn string_check(&mut self, quote: char) {
+        let mut value = String::new();
+        let mut valid = true;
+        while self.peek() != quote && !self.is_at_end() {
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push((quote, 0));
+                if valid {
+                    self.add_token(TokenType::INTERPOLATION, Object::Str(value));
+                }
+                return;
+            }
+            match self.advance() {
+                '\\' if !self.is_at_end() => match self.escape() {
+                    Some(c) => value.push(c),
+                    None => valid = false,
+                },
+                c => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(c);
+                }
+            }
+        }
         if self.is_at_end() {
-            return '\0';
-        };
-        self.source
-            .chars()
-            .nth(self.current)
-            .expect("error: scanner.peek")
+            self.incomplete(self.line, "Unterminated string!");
+            return;
+        }
+        // The closing ".
+        self.advance();
+        if valid {
+            self.add_token(TokenType::STRING, Object::Str(value));
+        }
+    }
+
+    /// `/* ... */`, which may contain other block comments.
+    fn block_comment(&mut self) {
+        let line = self.line;
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                self.incomplete(line, "Unterminated block comment.");
+                return;
+            }
+            match self.advance() {
+                '/' if self.match_next('*') => depth += 1,
+                '*' if self.match_next('/') => depth -= 1,
+                '\n' => self.line += 1,
+                _ => {}
+            }
+        }
     }
 
-    fn string_check(&mut self, string_char: char) {
-        while self.peek() != string_char && !self.is_at_end() {
-            if self.peek() == '\n' {
+    /// `"""..."""`, a multi-line string taken as written, less the indentation
+    /// its lines have in common.
+    fn text_block(&mut self) {
+        let line = self.line;
+        let mut text = String::new();
+        loop {
+            if self.is_at_end() {
+                self.incomplete(line, "Unterminated string!");
+                return;
+            }
+            if self.peek() == '"' && self.peek_next() == '"' && self.match_ahead(2, '"') {
+                break;
+            }
+            let c = self.advance();
+            if c == '\n' {
                 self.line += 1;
             }
+            text.push(c);
+        }
+        for _ in 0..3 {
             self.advance();
         }
+        self.add_token(TokenType::STRING, Object::Str(dedent(&text)));
+    }
+
+    /// `r"..."`, a string where backslashes have no special meaning.
+    fn raw_string(&mut self, quote: char) {
+        let mut value = String::new();
+        while self.peek() != quote && !self.is_at_end() {
+            let c = self.advance();
+            if c == '\n' {
+                self.line += 1;
+            }
+            value.push(c);
+        }
         if self.is_at_end() {
-            self.error.send(self.line, "Unterminated string!");
+            self.incomplete(self.line, "Unterminated string!");
             return;
         }
-        // The closing ".
         self.advance();
-        let value = &self.source[self.start + 1..self.current - 1];
-        self.add_token(TokenType::STRING, Object::Str(value.to_owned()));
+        self.add_token(TokenType::STRING, Object::Str(value));
+    }
+
+    /// Reads the escape sequence after a `\`, reporting it if invalid.
+    fn escape(&mut self) -> Option<char> {
+        let c = match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c @ ('\\' | '"' | '\'' | '$') => c,
+            'u' => return self.unicode_escape(),
+            c => {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                let message = format!("Unknown escape sequence '\\{}'.", c.escape_default());
+                self.error(self.line, &message);
+                return None;
+            }
+        };
+        Some(c)
+    }
+
+    /// `\u{1F600}`, one to six hex digits naming a Unicode scalar value.
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.match_next('{') {
+            self.error(self.line, "Expect '{' after '\\u'.");
+            return None;
+        }
+        let mut hex = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            hex.push(self.advance());
+        }
+        if !self.match_next('}') || !(1..=6).contains(&hex.len()) {
+            self.error(self.line, "Expect 1 to 6 hex digits in '\\u{...}'.");
+            return None;
+        }
+        let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+        if c.is_none() {
+            let message = format!("Invalid Unicode code point '\\u{{{hex}}}'.");
+            self.error(self.line, &message);
+        }
+        c
     }
 
     fn number_check(&mut self) {
-        while self.peek().is_numeric() {
+        let radix = match self.peek() {
+            _ if &self.source[self.start..self.current] != "0" => 10,
+            'x' | 'X' => 16,
+            'o' | 'O' => 8,
+            'b' | 'B' => 2,
+            _ => 10,
+        };
+        if radix == 10 {
+            return self.decimal_number();
+        }
+        self.advance();
+        let digits_start = self.current;
+        self.rest_of_number();
+        let prefix = &self.source[self.start..digits_start];
+        let text = &self.source[digits_start..self.current];
+        if text.is_empty() {
+            let message = format!("Expect digits after '{prefix}'.");
+            return self.error(self.line, &message);
+        }
+        if let Some(c) = text.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+            let message = format!("Invalid digit '{c}' in number '{prefix}{text}'.");
+            return self.error(self.line, &message);
+        }
+        match without_underscores(text, radix) {
+            Some(digits) => self.add_int(&digits, radix),
+            None => self.error(self.line, "Underscores must be between digits."),
+        }
+    }
+
+    /// `123`, `1_000`, `1.5`, `1e-9` or `2.5E3`.
+    fn decimal_number(&mut self) {
+        self.digits();
+        let mut float = false;
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            float = true;
             self.advance();
+            self.digits();
         }
-        if self.peek() == '.' && self.peek_next().is_numeric() {
-            // consume the "."
+        if matches!(self.peek(), 'e' | 'E') {
+            float = true;
             self.advance();
-            while self.peek().is_numeric() {
+            if matches!(self.peek(), '+' | '-') {
                 self.advance();
             }
+            if !self.peek().is_ascii_digit() {
+                self.rest_of_number();
+                let message = format!(
+                    "Expect digits in the exponent of '{}'.",
+                    &self.source[self.start..self.current]
+                );
+                return self.error(self.line, &message);
+            }
+            self.digits();
+        }
+        if is_xid_continue(self.peek()) {
+            self.rest_of_number();
+            let message = format!(
+                "Invalid number '{}'.",
+                &self.source[self.start..self.current]
+            );
+            return self.error(self.line, &message);
+        }
+
+        let text = &self.source[self.start..self.current];
+        let Some(text) = without_underscores(text, 10) else {
+            return self.error(self.line, "Underscores must be between digits.");
+        };
+        if !float {
+            return self.add_int(&text, 10);
+        }
+        match text.parse::<f64>() {
+            Ok(n) => self.add_token(TokenType::NUMBER, Object::Num(n)),
+            Err(_) => self.error(self.line, "Invalid number."),
+        }
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    /// Skips what is left of a malformed number, so it is reported once.
+    fn rest_of_number(&mut self) {
+        while is_xid_continue(self.peek()) {
+            self.advance();
+        }
+    }
+
+    fn add_int(&mut self, digits: &str, radix: u32) {
+        match i64::from_str_radix(digits, radix) {
+            Ok(n) => self.add_token(TokenType::NUMBER, Object::Int(n)),
+            #[cfg(feature = "bigint")]
+            Err(_) => {
+                let n = crate::bigint::BigInt::parse_radix(digits, radix).unwrap();
+                self.add_token(TokenType::NUMBER, crate::num::from_big(n))
+            }
+            #[cfg(not(feature = "bigint"))]
+            Err(_) => self.error(self.line, "Integer literal too large."),
         }
-        let n = self.source[self.start..self.current]
-            .parse::<f64>()
-            .expect("cannot parse {} to floot number");
-        self.add_token(TokenType::NUMBER, Object::Num(n));
+    }
+
+    /// True if the character `n` places after the current one is `expected`.
+    fn match_ahead(&self, n: usize, expected: char) -> bool {
+        self.rest().nth(n) == Some(expected)
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        };
-        self.source
-            .chars()
-            .nth(self.current + 1)
-            .expect("Error: scanner.peek_next: cannot read char at {}")
+        self.rest().nth(1).unwrap_or('\0')
     }
 
+    /// Identifiers follow Unicode's XID rules, like Rust's, with `_` allowed
+    /// at the start.
     fn identifier_check(&mut self) {
-        while self.peek().is_alphanumeric() {
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
         let text = &self.source[self.start..self.current];
         let token_type = KEYWORDS.with(|kw| {
             let keywords = kw.borrow();
-            let tt = keywords
-                .get(&text)
-                .unwrap_or(&TokenType::IDENTIFIER)
-                .clone();
-            tt
+            *keywords.get(&text).unwrap_or(&TokenType::IDENTIFIER)
         });
         self.add_none_token(token_type);
     }
 }
+
+/// Drops the `_` separators of a number, `None` if one is not between two digits.
+fn without_underscores(text: &str, radix: u32) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let is_digit = |at: Option<&char>| at.is_some_and(|c| c.is_digit(radix));
+    for (at, _) in chars.iter().enumerate().filter(|(_, &c)| c == '_') {
+        if at == 0 || !is_digit(chars.get(at - 1)) || !is_digit(chars.get(at + 1)) {
+            return None;
+        }
+    }
+    Some(text.replace('_', ""))
+}
+
+/// Text of a `"""` string: a line break right after the opening quotes is
+/// dropped, and so is the indentation common to all lines. When the closing
+/// quotes are on their own line, their indentation counts too. Indentation is
+/// common when the lines start with the same whitespace characters, so tabs
+/// and spaces do not mix.
+fn dedent(text: &str) -> String {
+    let text = text.strip_prefix('\n').unwrap_or(text);
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let closing = lines
+        .last()
+        .copied()
+        .filter(|line| lines.len() > 1 && line.trim().is_empty());
+    if closing.is_some() {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .chain(closing)
+        .reduce(common_prefix)
+        .unwrap_or("");
+    // Blank lines may be shorter than the indentation, or differ from it.
+    let mut result = lines
+        .iter()
+        .map(|line| line[common_prefix(line, indent).len()..].trim_end_matches('\r'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if closing.is_some() {
+        result.push('\n');
+    }
+    result
+}
+
+/// The longest start of `a` that `b` also starts with, in whole characters.
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .chars()
+        .zip(b.chars())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x.len_utf8())
+        .sum();
+    &a[..len]
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Result<Token, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token);
+            }
+            if self.done {
+                return None;
+            }
+            if self.is_at_end() {
+                if !self.interpolations.is_empty() {
+                    self.interpolations.clear();
+                    self.incomplete(self.line, "Unterminated string interpolation.");
+                }
+                self.start = self.current;
+                self.add_none_token(TokenType::EOF);
+                self.done = true;
+            } else {
+                self.start = self.current;
+                self.scan_token();
+            }
+        }
+    }
+}
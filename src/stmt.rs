@@ -8,5 +8,18 @@ pub enum Stmt {
     VarStmt(Token, Option<Result<Box<Expr>, Error>>),
     BlockStmt(Vec<Box<Stmt>>),
     IfStmt(Result<Box<Expr>, Error>, Box<Stmt>, Option<Box<Stmt>>),
-    WhileStmt(Result<Box<Expr>, Error>, Box<Stmt>),
+    /// Condition, body, and the increment of a desugared `for`, which
+    /// still runs after a `continue`.
+    WhileStmt(
+        Result<Box<Expr>, Error>,
+        Box<Stmt>,
+        Option<Result<Box<Expr>, Error>>,
+    ),
+    BreakStmt(Token),
+    ContinueStmt(Token),
+    ThrowStmt(Token, Result<Box<Expr>, Error>),
+
+    /// Body, the `catch` variable and block, and the `finally` block.
+    TryStmt(Box<Stmt>, Option<(Token, Box<Stmt>)>, Option<Box<Stmt>>),
+    ForInStmt(Token, Result<Box<Expr>, Error>, Box<Stmt>),
 }
@@ -0,0 +1,278 @@
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    iter, list,
+    native::NativeFn,
+    token::{Object, Token},
+};
+
+/// Methods of string values, the string itself is passed as the first
+/// argument. Lengths and positions count characters, not bytes.
+pub const METHODS: &[NativeFn] = &[
+    NativeFn {
+        name: "len",
+        arity: Some(0),
+        func: len,
+    },
+    NativeFn {
+        name: "upper",
+        arity: Some(0),
+        func: upper,
+    },
+    NativeFn {
+        name: "lower",
+        arity: Some(0),
+        func: lower,
+    },
+    NativeFn {
+        name: "trim",
+        arity: Some(0),
+        func: trim,
+    },
+    NativeFn {
+        name: "split",
+        arity: None,
+        func: split,
+    },
+    NativeFn {
+        name: "join",
+        arity: Some(1),
+        func: join,
+    },
+    NativeFn {
+        name: "replace",
+        arity: Some(2),
+        func: replace,
+    },
+    NativeFn {
+        name: "find",
+        arity: Some(1),
+        func: find,
+    },
+    NativeFn {
+        name: "starts_with",
+        arity: Some(1),
+        func: starts_with,
+    },
+    NativeFn {
+        name: "ends_with",
+        arity: Some(1),
+        func: ends_with,
+    },
+    NativeFn {
+        name: "chars",
+        arity: Some(0),
+        func: chars,
+    },
+    NativeFn {
+        name: "substring",
+        arity: None,
+        func: substring,
+    },
+    NativeFn {
+        name: "repeat",
+        arity: Some(1),
+        func: repeat,
+    },
+    NativeFn {
+        name: "format",
+        arity: None,
+        func: format,
+    },
+    NativeFn {
+        name: "parse_num",
+        arity: Some(0),
+        func: parse_num,
+    },
+    iter::ITER,
+];
+
+fn text(args: &[Object]) -> &str {
+    match &args[0] {
+        Object::Str(s) => s,
+        _ => unreachable!("string method called on {}", args[0].type_name()),
+    }
+}
+
+/// The argument at `at`, which must be a string.
+fn str_arg<'a>(paren: &Token, args: &'a [Object], at: usize) -> Result<&'a str, RuntimeError> {
+    match &args[at] {
+        Object::Str(s) => Ok(s),
+        other => Err(RuntimeError::new(
+            paren,
+            &format!("Expect a string argument but got {}.", other.type_name()),
+        )),
+    }
+}
+
+fn len(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(text(&args).chars().count() as i64))
+}
+
+fn upper(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(text(&args).to_uppercase()))
+}
+
+fn lower(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(text(&args).to_lowercase()))
+}
+
+fn trim(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(text(&args).trim().to_string()))
+}
+
+/// `s.split()` on runs of whitespace, or `s.split(sep)`.
+fn split(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let s = text(&args);
+    let parts: Vec<&str> = match args.len() {
+        1 => s.split_whitespace().collect(),
+        2 => match str_arg(paren, &args, 1)? {
+            "" => return Err(RuntimeError::new(paren, "Separator cannot be empty.")),
+            sep => s.split(sep).collect(),
+        },
+        n => {
+            return Err(RuntimeError::new(
+                paren,
+                &format!("Expected 0 or 1 arguments but got {}.", n - 1),
+            ))
+        }
+    };
+    Ok(Object::list(
+        parts.into_iter().map(|p| Object::Str(p.into())).collect(),
+    ))
+}
+
+/// `sep.join(list)`, the items as `print` shows them with `sep` between.
+fn join(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let Object::List(items) = &args[1] else {
+        return Err(RuntimeError::new(paren, "Can only join a list."));
+    };
+    let items: Vec<String> = items.borrow().iter().map(Object::to_string).collect();
+    Ok(Object::Str(items.join(text(&args))))
+}
+
+fn replace(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let from = str_arg(paren, &args, 1)?;
+    let to = str_arg(paren, &args, 2)?;
+    if from.is_empty() {
+        return Err(RuntimeError::new(paren, "Cannot replace an empty string."));
+    }
+    Ok(Object::Str(text(&args).replace(from, to)))
+}
+
+/// `s.find(part)`, the position of the first `part` in `s`, or null.
+fn find(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let s = text(&args);
+    Ok(match s.find(str_arg(paren, &args, 1)?) {
+        Some(at) => Object::Int(s[..at].chars().count() as i64),
+        None => Object::Null,
+    })
+}
+
+fn starts_with(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(
+        text(&args).starts_with(str_arg(paren, &args, 1)?),
+    ))
+}
+
+fn ends_with(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Bool(
+        text(&args).ends_with(str_arg(paren, &args, 1)?),
+    ))
+}
+
+fn chars(_: &mut Evaluate, _: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::list(
+        text(&args)
+            .chars()
+            .map(|c| Object::Str(c.to_string()))
+            .collect(),
+    ))
+}
+
+/// `s.substring(start)` or `s.substring(start, end)`, clamped like `slice`.
+fn substring(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    if !(2..=3).contains(&args.len()) {
+        return Err(RuntimeError::new(
+            paren,
+            &format!("Expected 1 or 2 arguments but got {}.", args.len() - 1),
+        ));
+    }
+    let s = text(&args);
+    let len = s.chars().count();
+    let start = list::bound(paren, &args[1], len)?;
+    let end = match args.get(2) {
+        Some(end) => list::bound(paren, end, len)?,
+        None => len,
+    };
+    Ok(Object::Str(
+        s.chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect(),
+    ))
+}
+
+fn repeat(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    Evaluate::repeat(paren, text(&args), &args[1])
+}
+
+/// `"{} and {}".format(a, b)` fills each `{}` with the next argument as
+/// `print` shows it. `{{` and `}}` stand for the braces themselves.
+fn format(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let mut values = args[1..].iter();
+    let mut result = String::new();
+    let mut chars = text(&args).chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                result.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                let Some(value) = values.next() else {
+                    return Err(RuntimeError::new(
+                        paren,
+                        "Not enough arguments for the format string.",
+                    ));
+                };
+                result.push_str(&value.to_string());
+            }
+            ('{' | '}', _) => {
+                return Err(RuntimeError::new(
+                    paren,
+                    "Unmatched brace in format string, write '{{' or '}}'.",
+                ))
+            }
+            _ => result.push(c),
+        }
+    }
+    if values.next().is_some() {
+        return Err(RuntimeError::new(
+            paren,
+            "Too many arguments for the format string.",
+        ));
+    }
+    Ok(Object::Str(result))
+}
+
+/// `s.parse_num()`, an Int if `s` is written as one, else a float.
+fn parse_num(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let s = text(&args).trim();
+    if let Ok(n) = s.parse::<i64>() {
+        return Ok(Object::Int(n));
+    }
+    #[cfg(feature = "bigint")]
+    if let Some(n) = crate::bigint::BigInt::parse(s) {
+        return Ok(crate::num::from_big(n));
+    }
+    // Rust also accepts words such as "inf" and "NaN", which are not numbers here.
+    match s.parse::<f64>() {
+        Ok(n) if s.bytes().any(|b| b.is_ascii_digit()) => Ok(Object::Num(n)),
+        _ => Err(RuntimeError::new(
+            paren,
+            &format!("Cannot parse {} as a number.", args[0].repr()),
+        )),
+    }
+}
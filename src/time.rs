@@ -0,0 +1,348 @@
+use std::{
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::RuntimeError,
+    eval::Evaluate,
+    native::NativeFn,
+    token::{Object, Token},
+};
+
+/// Where scripts get the time from. A host can swap in its own with
+/// `Mlox::set_clock`, e.g. a fake one so tests are deterministic.
+pub trait Clock {
+    /// Milliseconds since the Unix epoch.
+    fn now(&self) -> i64;
+
+    /// Seconds since a fixed point, for measuring how long something takes.
+    fn elapsed(&self) -> f64;
+
+    /// Waits `ms` milliseconds.
+    fn sleep(&self, ms: u64);
+}
+
+/// The clock of the machine.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        }
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    fn sleep(&self, ms: u64) {
+        thread::sleep(Duration::from_millis(ms));
+    }
+}
+
+/// Global time functions.
+pub const FUNCTIONS: &[NativeFn] = &[
+    NativeFn {
+        name: "clock",
+        arity: Some(0),
+        func: clock,
+    },
+    NativeFn {
+        name: "now",
+        arity: Some(0),
+        func: now,
+    },
+    NativeFn {
+        name: "sleep",
+        arity: Some(1),
+        func: sleep,
+    },
+    NativeFn {
+        name: "format_time",
+        arity: Some(1),
+        func: format_time,
+    },
+    NativeFn {
+        name: "parse_time",
+        arity: Some(1),
+        func: parse_time,
+    },
+];
+
+/// `clock()`, seconds as a float, to time parts of a script.
+fn clock(eval: &mut Evaluate, _: &Token, _: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Num(eval.clock().elapsed()))
+}
+
+/// `now()`, milliseconds since the Unix epoch.
+fn now(eval: &mut Evaluate, _: &Token, _: Vec<Object>) -> Result<Object, RuntimeError> {
+    Ok(Object::Int(eval.clock().now()))
+}
+
+/// Longest wait between two chances for the host to cancel a `sleep`.
+const SLEEP_SLICE: u64 = 50;
+
+/// `sleep(ms)` waits, in slices so the host can still cancel or pause the script.
+fn sleep(eval: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let mut ms = match args[0] {
+        Object::Int(ms) if ms >= 0 => ms as u64,
+        Object::Num(ms) if ms >= 0.0 => ms.round() as u64,
+        _ => {
+            return Err(RuntimeError::new(
+                paren,
+                "Sleep time must be a non-negative number of milliseconds.",
+            ))
+        }
+    };
+    while ms > 0 {
+        let slice = ms.min(SLEEP_SLICE);
+        eval.clock().sleep(slice);
+        ms -= slice;
+        eval.step()?;
+    }
+    Ok(Object::Null)
+}
+
+/// `format_time(ms)`, an ISO-8601 UTC timestamp such as
+/// `2024-03-01T12:30:00.000Z` for milliseconds since the Unix epoch.
+fn format_time(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let Object::Int(ms) = args[0] else {
+        return Err(RuntimeError::new(
+            paren,
+            "Time must be an integer number of milliseconds.",
+        ));
+    };
+    let days = ms.div_euclid(MS_PER_DAY);
+    let ms = ms.rem_euclid(MS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    if !(0..=9999).contains(&year) {
+        return Err(RuntimeError::new(paren, "Time is out of range."));
+    }
+    Ok(Object::Str(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )))
+}
+
+/// `parse_time(text)`, milliseconds since the Unix epoch for an ISO-8601
+/// date or date and time. A time without an offset is taken as UTC.
+fn parse_time(_: &mut Evaluate, paren: &Token, args: Vec<Object>) -> Result<Object, RuntimeError> {
+    let Object::Str(text) = &args[0] else {
+        return Err(RuntimeError::new(paren, "Timestamp must be a string."));
+    };
+    iso_8601(text)
+        .map(Object::Int)
+        .ok_or_else(|| RuntimeError::new(paren, &format!("Invalid ISO-8601 timestamp {text:?}.")))
+}
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// Reads `YYYY-MM-DD`, optionally followed by `THH:MM`, `:SS`, a fraction
+/// of a second, and `Z` or an offset like `+02:00`.
+fn iso_8601(text: &str) -> Option<i64> {
+    let mut rest = Cursor(text.trim());
+    let year = rest.number(4)?;
+    rest.expect('-')?;
+    let month = rest.number(2)?;
+    rest.expect('-')?;
+    let day = rest.number(2)?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * MS_PER_DAY;
+    if rest.0.is_empty() {
+        return Some(ms);
+    }
+    if !(rest.eat('T') || rest.eat(' ')) {
+        return None;
+    }
+    let hour = rest.number(2)?;
+    rest.expect(':')?;
+    let minute = rest.number(2)?;
+    let mut second = 0;
+    let mut millis = 0;
+    if rest.eat(':') {
+        second = rest.number(2)?;
+        if rest.eat('.') || rest.eat(',') {
+            let digits = rest.digits();
+            if digits.is_empty() {
+                return None;
+            }
+            // Digits past milliseconds are dropped.
+            millis = format!("{:0<3}", &digits[..digits.len().min(3)])
+                .parse()
+                .ok()?;
+        }
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    ms += ((hour * 60 + minute) * 60 + second) * 1000 + millis;
+    if rest.eat('Z') || rest.0.is_empty() {
+        return rest.0.is_empty().then_some(ms);
+    }
+    let sign = if rest.eat('+') {
+        1
+    } else if rest.eat('-') {
+        -1
+    } else {
+        return None;
+    };
+    let offset_hours = rest.number(2)?;
+    rest.eat(':');
+    let offset_minutes = rest.number(2)?;
+    if !rest.0.is_empty() || offset_hours > 23 || offset_minutes > 59 {
+        return None;
+    }
+    Some(ms - sign * (offset_hours * 60 + offset_minutes) * 60_000)
+}
+
+/// What is left of a timestamp being read.
+struct Cursor<'a>(&'a str);
+
+impl<'a> Cursor<'a> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.0.strip_prefix(c) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.eat(c).then_some(())
+    }
+
+    fn digits(&mut self) -> &'a str {
+        let end = self
+            .0
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let (digits, rest) = self.0.split_at(end);
+        self.0 = rest;
+        digits
+    }
+
+    /// Exactly `len` digits.
+    fn number(&mut self, len: usize) -> Option<i64> {
+        let digits = self.0.get(..len)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.0 = &self.0[len..];
+        digits.parse().ok()
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to a date of the proleptic Gregorian calendar, after
+/// Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date `days` after 1970-01-01, the inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_8601_reads_dates_times_and_offsets() {
+        assert_eq!(iso_8601("1970-01-01"), Some(0));
+        assert_eq!(
+            iso_8601("2024-03-01T12:30:00.000Z"),
+            Some(1_709_296_200_000)
+        );
+        assert_eq!(iso_8601("2024-03-01 12:30"), Some(1_709_296_200_000));
+        assert_eq!(
+            iso_8601("2024-03-01T14:30:00+02:00"),
+            Some(1_709_296_200_000)
+        );
+        assert_eq!(iso_8601("2024-03-01T07:00-0530"), Some(1_709_296_200_000));
+        assert_eq!(iso_8601("1970-01-01T00:00:00.1239Z"), Some(123));
+        assert_eq!(iso_8601("1969-12-31T23:59:59.5Z"), Some(-500));
+        assert_eq!(iso_8601("2000-02-29"), Some(951_782_400_000));
+    }
+
+    #[test]
+    fn iso_8601_rejects_malformed_text() {
+        for text in [
+            "",
+            "2024-3-01",
+            "2023-02-29",
+            "1900-02-29",
+            "2024-13-01",
+            "2024-01-01T24:00",
+            "2024-01-01T12",
+            "2024-01-01T12:00:00.",
+            "2024-01-01T12:00Zjunk",
+            "2024-01-01T12:00+2",
+        ] {
+            assert_eq!(iso_8601(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(days_from_civil(0, 1, 1)), (0, 1, 1));
+        for days in (-800_000..800_000).step_by(97) {
+            let (year, month, day) = civil_from_days(days);
+            assert!((1..=days_in_month(year, month)).contains(&day));
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -1,3 +1,16 @@
+use std::{cell::RefCell, ops::Range as Span, rc::Rc};
+
+#[cfg(feature = "bigint")]
+use crate::bigint::BigInt;
+use crate::{
+    error::ErrorValue,
+    iter::Iter,
+    map::Map,
+    native::{self, Module, NativeFn},
+    num,
+    range::Range,
+};
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TokenType {
     // Single-character tokens.
@@ -5,6 +18,9 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    COLON,
     COMMA,
     DOT,
     MINUS,
@@ -12,6 +28,7 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
 
     // One or two character tokens.
     BANG,
@@ -23,54 +40,266 @@ pub enum TokenType {
     LESS,
     LessEqual,
 
+    DotDot,
+    DotDotEqual,
+    StarStar,
+
     // Literals.
     IDENTIFIER,
     STRING,
+
+    /// The text of a string up to a `${`, the embedded expression follows.
+    INTERPOLATION,
     NUMBER,
 
     // Keywords.
     AND,
+    BREAK,
+    CATCH,
     CLASS,
+    CONTINUE,
     ELSE,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
     IF,
+    IN,
     Null,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
 
     EOF,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Object {
     Str(String),
+    Int(i64),
+
+    /// An integer too large for `Int`, never one that fits in it.
+    #[cfg(feature = "bigint")]
+    BigInt(Rc<BigInt>),
     Num(f64),
     Bool(bool),
     Null,
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<Map>>),
+    Range(Range),
+    Iterator(Rc<RefCell<Iter>>),
+    Error(Rc<ErrorValue>),
+    Native(Rc<NativeFn>),
+
+    /// A native method bound to the value it was looked up on.
+    Method(Box<Object>, Rc<NativeFn>),
+    Module(Rc<Module>),
 }
 impl Object {
+    pub fn list(items: Vec<Object>) -> Self {
+        Self::List(Rc::new(RefCell::new(items)))
+    }
+    pub fn map(map: Map) -> Self {
+        Self::Map(Rc::new(RefCell::new(map)))
+    }
+    pub fn iterator(iter: Iter) -> Self {
+        Self::Iterator(Rc::new(RefCell::new(iter)))
+    }
+    pub fn native(native: NativeFn) -> Self {
+        Self::Native(Rc::new(native))
+    }
     pub fn to_string(&self) -> String {
+        self.show(&mut vec![])
+    }
+    /// Like `to_string`, but quotes strings, as they are shown inside collections.
+    pub fn repr(&self) -> String {
+        self.repr_in(&mut vec![])
+    }
+    /// `to_string`, printing a list or map that is already `open` further out,
+    /// i.e. one that contains itself, as `[...]` or `{...}`.
+    fn show(&self, open: &mut Vec<*const ()>) -> String {
         match self {
             Self::Null => "None".into(),
             Self::Str(v) => v.into(),
+            Self::Int(n) => n.to_string(),
+            #[cfg(feature = "bigint")]
+            Self::BigInt(n) => n.to_string(),
+            // A whole float keeps its ".0" so it reads differently from an integer.
+            Self::Num(n) if n.fract() == 0.0 => format!("{n:.1}"),
             Self::Num(n) => n.to_string(),
             Self::Bool(b) => b.to_string(),
+            Self::List(items) => {
+                let at = Rc::as_ptr(items) as *const ();
+                if open.contains(&at) {
+                    return "[...]".into();
+                }
+                open.push(at);
+                let items = items
+                    .borrow()
+                    .iter()
+                    .map(|item| item.repr_in(open))
+                    .collect::<Vec<_>>();
+                open.pop();
+                format!("[{}]", items.join(", "))
+            }
+            Self::Map(map) => {
+                let at = Rc::as_ptr(map) as *const ();
+                if open.contains(&at) {
+                    return "{...}".into();
+                }
+                open.push(at);
+                let entries = map
+                    .borrow()
+                    .entries()
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.repr_in(open), v.repr_in(open)))
+                    .collect::<Vec<_>>();
+                open.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Self::Range(range) => range.to_string(),
+            Self::Iterator(_) => "<iterator>".into(),
+            Self::Error(error) => format!("Error: {}", error.message),
+            Self::Native(f) | Self::Method(_, f) => format!("<native fn {}>", f.name),
+            Self::Module(module) => format!("<module {}>", module.name),
+        }
+    }
+    fn repr_in(&self, open: &mut Vec<*const ()>) -> String {
+        match self {
+            Self::Str(s) => format!("{s:?}"),
+            _ => self.show(open),
+        }
+    }
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null => "Null",
+            Self::Str(_) => "Str",
+            Self::Int(_) => "Int",
+            #[cfg(feature = "bigint")]
+            Self::BigInt(_) => "Int",
+            Self::Num(_) => "Num",
+            Self::Bool(_) => "Bool",
+            Self::List(_) => "List",
+            Self::Map(_) => "Map",
+            Self::Range(_) => "Range",
+            Self::Iterator(_) => "Iterator",
+            Self::Error(_) => "Error",
+            Self::Native(_) | Self::Method(..) => "Fun",
+            Self::Module(_) => "Module",
+        }
+    }
+    /// Names reachable with `value.name`, for the prompt's completion.
+    pub fn members(&self) -> Vec<String> {
+        let fields = match self {
+            Self::Error(_) => ErrorValue::FIELDS,
+            Self::Module(module) => return module.names(),
+            _ => &[],
+        };
+        fields
+            .iter()
+            .copied()
+            .chain(native::methods(self).iter().map(|method| method.name))
+            .map(str::to_owned)
+            .collect()
+    }
+    pub fn is_number(&self) -> bool {
+        match self {
+            Self::Int(_) | Self::Num(_) => true,
+            #[cfg(feature = "bigint")]
+            Self::BigInt(_) => true,
+            _ => false,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Int(n) => Some(n as f64),
+            #[cfg(feature = "bigint")]
+            Self::BigInt(ref n) => Some(n.to_f64()),
+            Self::Num(n) => Some(n),
+            _ => None,
         }
     }
     pub fn is_true(&self) -> bool {
         match *self {
             Self::Null => false,
             Self::Str(ref v) => v.len() > 1,
+            Self::Int(n) => n > 0,
+            #[cfg(feature = "bigint")]
+            Self::BigInt(ref n) => !n.is_negative(),
             Self::Num(n) => n > 0.0,
             Self::Bool(b) => b,
+            Self::List(ref items) => !items.borrow().is_empty(),
+            Self::Map(ref map) => !map.borrow().is_empty(),
+            Self::Range(range) => !range.is_empty(),
+            Self::Iterator(_) | Self::Error(_) => true,
+            Self::Native(_) | Self::Method(..) | Self::Module(_) => true,
+        }
+    }
+}
+
+/// Values compare by content, and an integer equals the float of the same value.
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other, &mut vec![])
+    }
+}
+
+impl Object {
+    /// `==`, taking two lists or maps already being compared further out to
+    /// be equal, so ones that contain themselves compare without recursing
+    /// forever.
+    fn equals(&self, other: &Self, open: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Self::Str(a), Self::Str(b)) => a == b,
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Num(a), Self::Num(b)) => a == b,
+            (Self::Int(a), Self::Num(b)) | (Self::Num(b), Self::Int(a)) => {
+                num::int_eq_float(*a, *b)
+            }
+            #[cfg(feature = "bigint")]
+            (Self::BigInt(a), Self::BigInt(b)) => a == b,
+            #[cfg(feature = "bigint")]
+            (Self::BigInt(a), Self::Num(b)) | (Self::Num(b), Self::BigInt(a)) => {
+                BigInt::from_f64(*b).is_some_and(|b| **a == b)
+            }
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Null, Self::Null) => true,
+            (Self::List(a), Self::List(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if open.contains(&pair) {
+                    return true;
+                }
+                open.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let equal =
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b, open));
+                open.pop();
+                equal
+            }
+            (Self::Map(a), Self::Map(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if open.contains(&pair) {
+                    return true;
+                }
+                open.push(pair);
+                let equal = a.borrow().eq_by(&b.borrow(), |a, b| a.equals(b, open));
+                open.pop();
+                equal
+            }
+            (Self::Range(a), Self::Range(b)) => a == b,
+            (Self::Iterator(a), Self::Iterator(b)) => Rc::ptr_eq(a, b),
+            (Self::Error(a), Self::Error(b)) => a == b,
+            (Self::Native(a), Self::Native(b)) => a == b,
+            (Self::Method(a, f), Self::Method(b, g)) => f == g && a.equals(b, open),
+            (Self::Module(a), Self::Module(b)) => Rc::ptr_eq(a, b),
+            _ => false,
         }
     }
 }
@@ -81,15 +310,25 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Object,
     pub line: usize,
+
+    /// Byte offsets of the lexeme in the source.
+    pub span: Span<usize>,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Object, line: usize) -> Self {
+    pub fn new(
+        token_type: TokenType,
+        lexeme: String,
+        literal: Object,
+        line: usize,
+        span: Span<usize>,
+    ) -> Self {
         Self {
             token_type,
             lexeme,
             literal,
             line,
+            span,
         }
     }
 }